@@ -0,0 +1,107 @@
+//! Access to dynamic wheel that is currently polled.
+//!
+//! Functions of this module let futures spawn tasks or find out their own id without passing
+//! [`WheelHandle`](../dy/struct.WheelHandle.html) around, which is useful for library code that
+//! doesn't know in which wheel it runs. They return [`NoWheelError`](struct.NoWheelError.html)
+//! when called outside of [`Wheel`](../dy/struct.Wheel.html).
+
+use alloc::boxed::Box;
+use core::fmt::{Display, Formatter};
+use core::future::Future;
+use crate::dy::{Algorithm, IdNum, SpawnParams};
+use crate::dy::dyn_future::DynamicFuture;
+use crate::utils::DropGuard;
+
+// pointer to dynamic wheel algorithm that is currently polled, null when outside of wheel
+#[cfg(feature = "std")]
+std::thread_local! {
+    static CURRENT: core::cell::Cell<*const ()> = core::cell::Cell::new(core::ptr::null());
+}
+#[cfg(feature = "std")]
+fn get_current() -> *const () { CURRENT.with(|c| c.get()) }
+#[cfg(feature = "std")]
+fn set_current(value: *const ()) { CURRENT.with(|c| c.set(value)) }
+
+// without std wheels are expected to run on single thread only
+#[cfg(not(feature = "std"))]
+static CURRENT: core::sync::atomic::AtomicPtr<()> = core::sync::atomic::AtomicPtr::new(core::ptr::null_mut());
+#[cfg(not(feature = "std"))]
+fn get_current() -> *const () { CURRENT.load(core::sync::atomic::Ordering::Relaxed) }
+#[cfg(not(feature = "std"))]
+fn set_current(value: *const ()) { CURRENT.store(value as *mut (), core::sync::atomic::Ordering::Relaxed) }
+
+/// Make given wheel current while function runs, restores previous one afterwards (wheels can be nested).
+pub(crate) fn enter<T>(alg: &Algorithm<'_>, func: impl FnOnce() -> T) -> T {
+    let prev = get_current();
+    set_current(alg as *const Algorithm<'_> as *const ());
+    let _guard = DropGuard::new(move || set_current(prev));
+    func()
+}
+
+pub(crate) fn with_current<T>(func: impl FnOnce(&Algorithm<'static>) -> T) -> Result<T, NoWheelError> {
+    let ptr = get_current();
+    if ptr.is_null() {
+        return Err(NoWheelError);
+    }
+    //SAFETY: pointer is set only while wheel is polled, so it points to live algorithm. Its lifetime
+    //is erased, but only 'static futures are inserted into it which outlive any lifetime.
+    Ok(func(unsafe { &*(ptr as *const Algorithm<'static>) }))
+}
+
+/// Error returned by [`spawn`](fn.spawn.html) and [`current_task`](fn.current_task.html) when
+/// called outside of dynamic [`Wheel`](../dy/struct.Wheel.html).
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct NoWheelError;
+
+impl Display for NoWheelError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str("Not called inside of wheel.")
+    }
+}
+
+/// Spawn task in [`Wheel`](../dy/struct.Wheel.html) that is currently polled, without passing
+/// [`WheelHandle`](../dy/struct.WheelHandle.html) around.
+///
+/// Task is created with default [`SpawnParams`](../dy/struct.SpawnParams.html), see
+/// [`spawn_with`](fn.spawn_with.html). Because wheel is not known statically, future must have
+/// static lifetime. Returns error when called outside of wheel, e.g when future that
+/// calls this function is polled by other executor. Works inside tasks of wheel and inside future
+/// passed to [`Wheel::run_until`](../dy/struct.Wheel.html#method.run_until).
+///
+/// # Examples
+/// ```
+/// use juggle::{dy::*, ambient::{self, current_task, NoWheelError}};
+///
+/// async fn start_worker() -> IdNum {
+///     ambient::spawn(async { /* ... */ }).expect("Not inside wheel.")
+/// }
+///
+/// let wheel = Wheel::new();
+/// wheel.handle().spawn_default(async {
+///     let worker = start_worker().await;
+///     assert_ne!(current_task(), Ok(worker));
+/// }).unwrap();
+/// smol::block_on(wheel).unwrap();
+/// assert_eq!(ambient::spawn(async {}), Err(NoWheelError));
+/// ```
+pub fn spawn<F>(future: F) -> Result<IdNum, NoWheelError> where F: Future<Output=()> + 'static {
+    spawn_with(SpawnParams::default(), future)
+}
+
+/// Spawn task with given parameters in [`Wheel`](../dy/struct.Wheel.html) that is currently polled.
+/// Works like [`spawn`](fn.spawn.html).
+pub fn spawn_with<P, F>(params: P, future: F) -> Result<IdNum, NoWheelError>
+    where F: Future<Output=()> + 'static, P: Into<SpawnParams> {
+    let params = params.into();
+    with_current(|alg| {
+        let dynamic = DynamicFuture::new(Box::pin(future), alg.clone_registry(), &params);
+        alg.register(dynamic, &params).map(IdNum::from_usize)
+    })?.ok_or(NoWheelError)
+}
+
+/// Returns id of currently executing task of [`Wheel`](../dy/struct.Wheel.html) that is currently polled.
+///
+/// Returns error when not called inside task of wheel.
+pub fn current_task() -> Result<IdNum, NoWheelError> {
+    with_current(|alg| alg.get_current().map(IdNum::from_usize))?.ok_or(NoWheelError)
+}
@@ -0,0 +1,125 @@
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use crate::utils::DropGuard;
+
+/// Number of budget units that each task receives when it's polled by the wheel.
+pub const POLL_BUDGET: u32 = 128;
+
+const UNLIMITED: u32 = u32::MAX;
+
+#[cfg(feature = "std")]
+std::thread_local! {
+    static BUDGET: core::cell::Cell<u32> = core::cell::Cell::new(UNLIMITED);
+}
+#[cfg(feature = "std")]
+fn get_budget() -> u32 { BUDGET.with(|b| b.get()) }
+#[cfg(feature = "std")]
+fn set_budget(value: u32) { BUDGET.with(|b| b.set(value)) }
+
+// without std wheels are expected to run on single thread only
+#[cfg(not(feature = "std"))]
+static BUDGET: core::sync::atomic::AtomicU32 = core::sync::atomic::AtomicU32::new(UNLIMITED);
+#[cfg(not(feature = "std"))]
+fn get_budget() -> u32 { BUDGET.load(core::sync::atomic::Ordering::Relaxed) }
+#[cfg(not(feature = "std"))]
+fn set_budget(value: u32) { BUDGET.store(value, core::sync::atomic::Ordering::Relaxed) }
+
+/// Run given function with fresh budget, restores previous budget afterwards (wheels can be nested).
+pub(crate) fn with_budget<T>(func: impl FnOnce() -> T) -> T {
+    let prev = get_budget();
+    set_budget(POLL_BUDGET);
+    let _guard = DropGuard::new(move || set_budget(prev));
+    func()
+}
+
+/// Poll operation only if current task has budget left and consume one unit when it's ready, so
+/// that waiting doesn't cost any budget. When budget is exhausted task is woken and operation
+/// isn't polled at all.
+pub(crate) fn poll_with_budget<T>(cx: &mut Context<'_>, func: impl FnOnce(&mut Context<'_>) -> Poll<T>) -> Poll<T> {
+    let budget = get_budget();
+    if budget == 0 {
+        cx.waker().wake_by_ref();
+        return Poll::Pending;
+    }
+    let result = func(cx);
+    if result.is_ready() && budget != UNLIMITED {
+        set_budget(get_budget().saturating_sub(1));
+    }
+    result
+}
+
+/// Consume one unit of cooperative budget of currently polled task.
+///
+/// Each time task is polled by the wheel it receives budget of [`POLL_BUDGET`](constant.POLL_BUDGET.html)
+/// units. Primitives that can be ready many times in a row (like [`Yield::none`](struct.Yield.html#method.none))
+/// consume budget, and when it's exhausted they return `Poll::Pending` and wake the task, so that
+/// task which loops over always-ready source still gives other tasks opportunity to execute.
+/// Use this function when implementing such primitives by hand. Returns `Poll::Ready(())` if
+/// budget was consumed. Outside of wheel budget is unlimited.
+///
+/// # Examples
+/// ```
+/// use juggle::poll_budget;
+/// use std::collections::VecDeque;
+/// use std::task::{Context, Poll};
+///
+/// fn poll_pop(queue: &mut VecDeque<u32>, cx: &mut Context<'_>) -> Poll<Option<u32>> {
+///     match poll_budget(cx) {
+///         Poll::Ready(()) => Poll::Ready(queue.pop_front()),
+///         Poll::Pending => Poll::Pending,
+///     }
+/// }
+/// ```
+pub fn poll_budget(cx: &mut Context<'_>) -> Poll<()> {
+    match get_budget() {
+        UNLIMITED => Poll::Ready(()),
+        0 => {
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+        n => {
+            set_budget(n - 1);
+            Poll::Ready(())
+        }
+    }
+}
+
+/// Returns remaining budget of currently polled task, or `None` when called outside of wheel.
+pub fn remaining_budget() -> Option<u32> {
+    match get_budget() {
+        UNLIMITED => None,
+        n => Some(n),
+    }
+}
+
+/// When awaited consumes one unit of cooperative budget, yields current task if budget is exhausted.
+///
+/// Async equivalent of [`poll_budget`](fn.poll_budget.html).
+///
+/// # Examples
+/// ```
+/// # use std::collections::VecDeque;
+/// use juggle::consume_budget;
+///
+/// async fn process(queue: &mut VecDeque<u32>) {
+///     while let Some(item) = queue.pop_front() {
+///         consume_budget().await; // let other tasks execute from time to time
+///         // process item...
+///     }
+/// }
+/// # smol::block_on(process(&mut VecDeque::from(vec![1, 2, 3])));
+/// ```
+pub fn consume_budget() -> ConsumeBudget { ConsumeBudget(()) }
+
+/// Future returned by [`consume_budget`](fn.consume_budget.html).
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct ConsumeBudget(());
+
+impl Future for ConsumeBudget {
+    type Output = ();
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        poll_budget(cx)
+    }
+}
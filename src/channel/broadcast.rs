@@ -0,0 +1,264 @@
+//! Multi-producer, multi-consumer channel where every value is received by all receivers.
+//!
+//! Channel keeps limited number of recent values. Receiver that is too slow misses the oldest
+//! values, it's then informed how many values it skipped by [`RecvError::Lagged`](enum.RecvError.html#variant.Lagged).
+//! New receivers are created by [`Sender::subscribe`](struct.Sender.html#method.subscribe) or by
+//! cloning existing ones.
+//!
+//! # Examples
+//! ```
+//! use juggle::{*, dy::*, channel::broadcast};
+//!
+//! let wheel = Wheel::new();
+//! let (tx, rx) = broadcast::channel(4);
+//! for mut rx in vec![rx, tx.subscribe()] {
+//!     wheel.handle().spawn_default(async move {
+//!         let mut received = Vec::new();
+//!         while let Ok(value) = rx.recv().await {
+//!             received.push(value);
+//!         }
+//!         assert_eq!(received, vec![1, 2, 3]);
+//!     }).unwrap();
+//! }
+//! wheel.handle().spawn_default(async move {
+//!     for i in 1..=3 {
+//!         assert_eq!(tx.send(i), Ok(2)); // number of receivers
+//!         yield_once!();
+//!     }
+//! }).unwrap();
+//! smol::block_on(wheel).unwrap();
+//! ```
+
+use alloc::collections::VecDeque;
+use alloc::rc::Rc;
+use core::cell::RefCell;
+use core::fmt::{Debug, Display, Formatter};
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use crate::channel::{wake_all, SendError, WakerList};
+use crate::budget::poll_with_budget;
+
+struct Shared<T> {
+    buffer: VecDeque<T>,
+    first: u64, //position of oldest value in buffer
+    capacity: usize,
+    senders: usize,
+    receivers: usize,
+    waiting: WakerList, //receivers waiting for new value
+}
+
+impl<T> Shared<T> {
+    fn end(&self) -> u64 { self.first + self.buffer.len() as u64 }
+}
+
+/// Create channel that keeps given number of most recent values.
+///
+/// # Panics
+/// Panics if capacity is zero.
+pub fn channel<T: Clone>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    assert_ne!(capacity, 0, "Channel capacity is zero.");
+    let shared = Rc::new(RefCell::new(Shared {
+        buffer: VecDeque::new(),
+        first: 0,
+        capacity,
+        senders: 1,
+        receivers: 1,
+        waiting: WakerList::new(),
+    }));
+    (Sender { shared: shared.clone() }, Receiver { shared, next: 0 })
+}
+
+/// Error returned by [`Receiver::recv`](struct.Receiver.html#method.recv).
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum RecvError {
+    /// All senders were dropped and receiver got all values.
+    Closed,
+    /// Receiver skipped given number of values that were overwritten before it received them,
+    /// next receive returns oldest value that is still kept.
+    Lagged(u64),
+}
+
+impl Display for RecvError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            RecvError::Closed => f.write_str("Channel is closed."),
+            RecvError::Lagged(n) => write!(f, "Receiver lagged by {} values.", n),
+        }
+    }
+}
+
+/// Error returned by [`Receiver::try_recv`](struct.Receiver.html#method.try_recv).
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum TryRecvError {
+    /// There is no new value yet.
+    Empty,
+    /// All senders were dropped and receiver got all values.
+    Closed,
+    /// Receiver skipped given number of values, see [`RecvError::Lagged`](enum.RecvError.html#variant.Lagged).
+    Lagged(u64),
+}
+
+impl Display for TryRecvError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TryRecvError::Empty => f.write_str("Channel is empty."),
+            TryRecvError::Closed => f.write_str("Channel is closed."),
+            TryRecvError::Lagged(n) => write!(f, "Receiver lagged by {} values.", n),
+        }
+    }
+}
+
+/// Sending side of [`broadcast`](index.html) channel, can be cloned.
+pub struct Sender<T> {
+    shared: Rc<RefCell<Shared<T>>>,
+}
+
+impl<T: Clone> Sender<T> {
+    /// Send value to all receivers, never waits. Returns number of receivers or given value back
+    /// if there are no receivers. When channel is full, oldest value is discarded.
+    pub fn send(&self, value: T) -> Result<usize, SendError<T>> {
+        let mut shared = self.shared.borrow_mut();
+        if shared.receivers == 0 {
+            return Err(SendError(value));
+        }
+        let old = if shared.buffer.len() == shared.capacity {
+            shared.first += 1;
+            shared.buffer.pop_front()
+        } else { None };
+        shared.buffer.push_back(value);
+        let receivers = shared.receivers;
+        let wakers = shared.waiting.take();
+        drop(shared);
+        drop(old); //value is dropped outside of borrow
+        wake_all(wakers);
+        Ok(receivers)
+    }
+    /// Create new receiver that gets values sent after this call.
+    pub fn subscribe(&self) -> Receiver<T> {
+        let mut shared = self.shared.borrow_mut();
+        shared.receivers += 1;
+        let next = shared.end();
+        drop(shared);
+        Receiver { shared: self.shared.clone(), next }
+    }
+    /// Returns number of receivers.
+    pub fn receiver_count(&self) -> usize { self.shared.borrow().receivers }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.shared.borrow_mut().senders += 1;
+        Self { shared: self.shared.clone() }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut shared = self.shared.borrow_mut();
+        shared.senders -= 1;
+        let wakers = if shared.senders == 0 { shared.waiting.take() } else { Default::default() };
+        drop(shared);
+        wake_all(wakers);
+    }
+}
+
+impl<T> Debug for Sender<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Sender").field("receivers", &self.shared.borrow().receivers).finish()
+    }
+}
+
+/// Receiving side of [`broadcast`](index.html) channel, can be cloned. Clone receives the same
+/// values as original receiver.
+pub struct Receiver<T> {
+    shared: Rc<RefCell<Shared<T>>>,
+    next: u64, //position of next value to receive
+}
+
+impl<T: Clone> Receiver<T> {
+    /// Receive next value, waiting until it's sent.
+    pub fn recv(&mut self) -> Recv<'_, T> { Recv { receiver: self, key: None } }
+    /// Receive next value if there is any, without waiting.
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        let shared = self.shared.borrow();
+        if self.next < shared.first {
+            let skipped = shared.first - self.next;
+            self.next = shared.first;
+            return Err(TryRecvError::Lagged(skipped));
+        }
+        match shared.buffer.get((self.next - shared.first) as usize) {
+            Some(value) => {
+                let value = value.clone();
+                self.next += 1;
+                Ok(value)
+            }
+            None if shared.senders == 0 => Err(TryRecvError::Closed),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+}
+
+impl<T> Receiver<T> {
+    /// Returns number of values that were sent but not received yet by this receiver.
+    pub fn len(&self) -> usize {
+        let shared = self.shared.borrow();
+        (shared.end() - self.next.max(shared.first)) as usize
+    }
+    /// Returns true if there are no values to receive.
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+}
+
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Self {
+        self.shared.borrow_mut().receivers += 1;
+        Self { shared: self.shared.clone(), next: self.next }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) { self.shared.borrow_mut().receivers -= 1; }
+}
+
+impl<T> Debug for Receiver<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Receiver").field("len", &self.len()).finish()
+    }
+}
+
+/// Future returned by [`Receiver::recv`](struct.Receiver.html#method.recv).
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Recv<'a, T> {
+    receiver: &'a mut Receiver<T>,
+    key: Option<usize>, //registration in list of waiting receivers
+}
+
+impl<T: Clone> Future for Recv<'_, T> {
+    type Output = Result<T, RecvError>;
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        poll_with_budget(cx, move |cx| {
+            match self.receiver.try_recv() {
+                Ok(value) => Poll::Ready(Ok(value)),
+                Err(TryRecvError::Closed) => Poll::Ready(Err(RecvError::Closed)),
+                Err(TryRecvError::Lagged(n)) => Poll::Ready(Err(RecvError::Lagged(n))),
+                Err(TryRecvError::Empty) => {
+                    let this = &mut *self;
+                    this.receiver.shared.borrow_mut().waiting.register(&mut this.key, cx.waker());
+                    Poll::Pending
+                }
+            }
+        })
+    }
+}
+
+impl<T> Drop for Recv<'_, T> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key {
+            self.receiver.shared.borrow_mut().waiting.remove(key);
+        }
+    }
+}
+
+impl<T> Debug for Recv<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result { f.write_str("Recv") }
+}
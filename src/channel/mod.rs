@@ -0,0 +1,133 @@
+//! Channels for exchanging data between tasks of single thread.
+//!
+//! Channels are meant for tasks of the same [`Wheel`](../dy/struct.Wheel.html) (or any other
+//! single-threaded executor), they are not `Send` and don't use atomics or locks. Waiting tasks
+//! are woken by their wakers only when they can make progress, so there is no need for busy
+//! yielding. When one side of channel is dropped, e.g because task that owned it was cancelled,
+//! other side observes that channel is closed. Completed sends and receives consume cooperative
+//! budget (see [`poll_budget`](../fn.poll_budget.html)), so task that drains busy channel still
+//! lets other tasks execute.
+//!
+//! * [`mpsc`](mpsc/index.html) - bounded or unbounded queue with many senders and one receiver.
+//! * [`oneshot`](oneshot/index.html) - single value sent from one task to another.
+//! * [`broadcast`](broadcast/index.html) - every value is received by all receivers.
+//! * [`watch`](watch/index.html) - receivers observe latest value.
+
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+use core::mem;
+use core::task::Waker;
+
+pub mod mpsc;
+pub mod oneshot;
+pub mod broadcast;
+pub mod watch;
+
+/// Error returned when value is sent to channel which has no receivers. Contains value that
+/// wasn't sent.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct SendError<T>(pub T);
+
+impl<T> Display for SendError<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str("Channel is closed.")
+    }
+}
+
+/// Error returned by `try_send` methods. Contains value that wasn't sent.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum TrySendError<T> {
+    /// Channel is full and sending would have to wait.
+    Full(T),
+    /// Channel has no receivers.
+    Closed(T),
+}
+
+impl<T> TrySendError<T> {
+    /// Returns value that wasn't sent.
+    pub fn into_inner(self) -> T {
+        match self {
+            TrySendError::Full(value) | TrySendError::Closed(value) => value,
+        }
+    }
+}
+
+impl<T> Display for TrySendError<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TrySendError::Full(_) => f.write_str("Channel is full."),
+            TrySendError::Closed(_) => f.write_str("Channel is closed."),
+        }
+    }
+}
+
+/// Error returned when receiving from channel whose senders were dropped.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct RecvError;
+
+impl Display for RecvError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str("Channel is closed.")
+    }
+}
+
+/// Error returned by `try_recv` methods.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum TryRecvError {
+    /// There is no value yet, but it might be sent later.
+    Empty,
+    /// There is no value and senders were dropped.
+    Closed,
+}
+
+impl Display for TryRecvError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TryRecvError::Empty => f.write_str("Channel is empty."),
+            TryRecvError::Closed => f.write_str("Channel is closed."),
+        }
+    }
+}
+
+// wakers of tasks waiting for the same event, woken all at once or one by one in order of registration
+pub(crate) struct WakerList {
+    wakers: Vec<(usize, Waker)>, //waker with key of its registration
+    next_key: usize,
+}
+
+impl WakerList {
+    pub(crate) const fn new() -> Self { Self { wakers: Vec::new(), next_key: 0 } }
+    // 'key' is kept by waiting future, so that future polled repeatedly is registered once and
+    // can remove its registration when dropped
+    pub(crate) fn register(&mut self, key: &mut Option<usize>, waker: &Waker) {
+        if let Some(entry) = key.and_then(|k| self.wakers.iter_mut().find(|(other, _)| *other == k)) {
+            if !entry.1.will_wake(waker) {
+                entry.1 = waker.clone();
+            }
+            return;
+        }
+        let new_key = self.next_key;
+        self.next_key = self.next_key.wrapping_add(1);
+        self.wakers.push((new_key, waker.clone()));
+        *key = Some(new_key);
+    }
+    // returns false if registration was already taken
+    pub(crate) fn remove(&mut self, key: usize) -> bool {
+        match self.wakers.iter().position(|(other, _)| *other == key) {
+            Some(index) => {
+                self.wakers.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+    // taken wakers must be woken after channel state is released, waker might poll other future
+    pub(crate) fn take(&mut self) -> Vec<Waker> {
+        mem::replace(&mut self.wakers, Vec::new()).into_iter().map(|(_, waker)| waker).collect()
+    }
+    pub(crate) fn take_one(&mut self) -> Option<Waker> {
+        if self.wakers.is_empty() { None } else { Some(self.wakers.remove(0).1) }
+    }
+}
+
+pub(crate) fn wake_all(wakers: Vec<Waker>) { wakers.into_iter().for_each(Waker::wake) }
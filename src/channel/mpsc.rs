@@ -0,0 +1,258 @@
+//! Multi-producer, single-consumer queue.
+//!
+//! Channel is created with [`channel`](fn.channel.html) (bounded) or
+//! [`unbounded`](fn.unbounded.html). Values are received in order in which they were sent.
+//!
+//! # Examples
+//! ```
+//! use juggle::{*, dy::*, channel::mpsc};
+//!
+//! let wheel = Wheel::new();
+//! let (tx, mut rx) = mpsc::channel(2);
+//! for i in 0..3 {
+//!     let tx = tx.clone();
+//!     wheel.handle().spawn_default(async move {
+//!         for j in 0..3 {
+//!             tx.send(i * 3 + j).await.unwrap(); // waits while channel is full
+//!         }
+//!     }).unwrap();
+//! }
+//! drop(tx);
+//! wheel.handle().spawn_default(async move {
+//!     let mut sum = 0;
+//!     while let Some(value) = rx.recv().await { // None when all senders were dropped
+//!         sum += value;
+//!     }
+//!     assert_eq!(sum, 36);
+//! }).unwrap();
+//! smol::block_on(wheel).unwrap();
+//! ```
+
+use alloc::collections::VecDeque;
+use alloc::rc::Rc;
+use core::cell::RefCell;
+use core::fmt::{Debug, Formatter};
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+use crate::channel::{wake_all, SendError, TryRecvError, TrySendError, WakerList};
+use crate::budget::poll_with_budget;
+
+struct Shared<T> {
+    queue: VecDeque<T>,
+    capacity: Option<usize>, //None if unbounded
+    senders: usize,
+    closed: bool, //receiver was dropped or closed
+    receiver: Option<Waker>,
+    blocked: WakerList, //senders waiting for free space
+}
+
+impl<T> Shared<T> {
+    fn is_full(&self) -> bool { self.capacity.map_or(false, |c| self.queue.len() >= c) }
+}
+
+/// Create bounded channel that holds at most given number of values. Senders wait until there is
+/// free space in channel.
+///
+/// # Panics
+/// Panics if capacity is zero.
+pub fn channel<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    assert_ne!(capacity, 0, "Channel capacity is zero.");
+    create(Some(capacity))
+}
+
+/// Create channel without limit on number of values, sending never waits.
+pub fn unbounded<T>() -> (Sender<T>, Receiver<T>) { create(None) }
+
+fn create<T>(capacity: Option<usize>) -> (Sender<T>, Receiver<T>) {
+    let shared = Rc::new(RefCell::new(Shared {
+        queue: VecDeque::new(),
+        capacity,
+        senders: 1,
+        closed: false,
+        receiver: None,
+        blocked: WakerList::new(),
+    }));
+    (Sender { shared: shared.clone() }, Receiver { shared })
+}
+
+/// Sending side of [`mpsc`](index.html) channel, can be cloned.
+pub struct Sender<T> {
+    shared: Rc<RefCell<Shared<T>>>,
+}
+
+impl<T> Sender<T> {
+    /// Send value, waiting until there is free space in channel. Returned future resolves to
+    /// error with given value if receiver was dropped.
+    pub fn send(&self, value: T) -> SendFuture<'_, T> { SendFuture { sender: self, value: Some(value), key: None } }
+    /// Send value if there is free space in channel, without waiting.
+    pub fn try_send(&self, value: T) -> Result<(), TrySendError<T>> {
+        let mut shared = self.shared.borrow_mut();
+        if shared.closed {
+            return Err(TrySendError::Closed(value));
+        }
+        if shared.is_full() {
+            return Err(TrySendError::Full(value));
+        }
+        shared.queue.push_back(value);
+        let waker = shared.receiver.take();
+        drop(shared);
+        if let Some(waker) = waker { waker.wake(); }
+        Ok(())
+    }
+    /// Returns true if receiver was dropped or closed.
+    pub fn is_closed(&self) -> bool { self.shared.borrow().closed }
+    /// Returns number of values waiting in channel.
+    pub fn len(&self) -> usize { self.shared.borrow().queue.len() }
+    /// Returns true if there are no values waiting in channel.
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.shared.borrow_mut().senders += 1;
+        Self { shared: self.shared.clone() }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut shared = self.shared.borrow_mut();
+        shared.senders -= 1;
+        let waker = if shared.senders == 0 { shared.receiver.take() } else { None };
+        drop(shared);
+        if let Some(waker) = waker { waker.wake(); }
+    }
+}
+
+impl<T> Debug for Sender<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Sender").field("len", &self.len()).field("closed", &self.is_closed()).finish()
+    }
+}
+
+/// Future returned by [`Sender::send`](struct.Sender.html#method.send).
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct SendFuture<'a, T> {
+    sender: &'a Sender<T>,
+    value: Option<T>,
+    key: Option<usize>, //registration in list of blocked senders
+}
+
+impl<T> Unpin for SendFuture<'_, T> {}
+
+impl<T> Future for SendFuture<'_, T> {
+    type Output = Result<(), SendError<T>>;
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        poll_with_budget(cx, move |cx| {
+            let value = self.value.take().expect("SendFuture polled after completion.");
+            match self.sender.try_send(value) {
+                Ok(()) => Poll::Ready(Ok(())),
+                Err(TrySendError::Closed(value)) => Poll::Ready(Err(SendError(value))),
+                Err(TrySendError::Full(value)) => {
+                    self.value = Some(value);
+                    let this = &mut *self;
+                    this.sender.shared.borrow_mut().blocked.register(&mut this.key, cx.waker());
+                    Poll::Pending
+                }
+            }
+        })
+    }
+}
+
+impl<T> Drop for SendFuture<'_, T> {
+    fn drop(&mut self) {
+        let key = match self.key {
+            Some(key) => key,
+            None => return, //never waited
+        };
+        let mut shared = self.sender.shared.borrow_mut();
+        let registered = shared.blocked.remove(key);
+        //this sender was woken to take free slot but didn't take it, pass it to next sender
+        let waker = if !registered && self.value.is_some() && !shared.is_full() { shared.blocked.take_one() } else { None };
+        drop(shared);
+        if let Some(waker) = waker { waker.wake(); }
+    }
+}
+
+impl<T> Debug for SendFuture<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result { f.write_str("SendFuture") }
+}
+
+/// Receiving side of [`mpsc`](index.html) channel.
+pub struct Receiver<T> {
+    shared: Rc<RefCell<Shared<T>>>,
+}
+
+impl<T> Receiver<T> {
+    /// Receive next value, waiting until it's sent. Returned future resolves to `None` when all
+    /// senders were dropped and there are no more values.
+    pub fn recv(&mut self) -> Recv<'_, T> { Recv { receiver: self } }
+    /// Receive next value if there is any, without waiting.
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        let mut shared = self.shared.borrow_mut();
+        match shared.queue.pop_front() {
+            Some(value) => {
+                let waker = shared.blocked.take_one(); //one slot freed, wake one sender
+                drop(shared);
+                if let Some(waker) = waker { waker.wake(); }
+                Ok(value)
+            }
+            None if shared.senders == 0 => Err(TryRecvError::Closed),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+    /// Close channel without dropping receiver. Senders can't send any more values, values that
+    /// were already sent can still be received.
+    pub fn close(&mut self) {
+        let mut shared = self.shared.borrow_mut();
+        shared.closed = true;
+        let wakers = shared.blocked.take();
+        drop(shared);
+        wake_all(wakers);
+    }
+    /// Returns number of values waiting in channel.
+    pub fn len(&self) -> usize { self.shared.borrow().queue.len() }
+    /// Returns true if there are no values waiting in channel.
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.close();
+        let values = core::mem::replace(&mut self.shared.borrow_mut().queue, VecDeque::new());
+        drop(values); //values are dropped outside of borrow
+    }
+}
+
+impl<T> Debug for Receiver<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Receiver").field("len", &self.len()).finish()
+    }
+}
+
+/// Future returned by [`Receiver::recv`](struct.Receiver.html#method.recv).
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Recv<'a, T> {
+    receiver: &'a mut Receiver<T>,
+}
+
+impl<T> Future for Recv<'_, T> {
+    type Output = Option<T>;
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        poll_with_budget(cx, move |cx| {
+            match self.receiver.try_recv() {
+                Ok(value) => Poll::Ready(Some(value)),
+                Err(TryRecvError::Closed) => Poll::Ready(None),
+                Err(TryRecvError::Empty) => {
+                    self.receiver.shared.borrow_mut().receiver = Some(cx.waker().clone());
+                    Poll::Pending
+                }
+            }
+        })
+    }
+}
+
+impl<T> Debug for Recv<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result { f.write_str("Recv") }
+}
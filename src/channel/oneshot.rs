@@ -0,0 +1,169 @@
+//! Channel for sending single value.
+//!
+//! [`Receiver`](struct.Receiver.html) is a future that resolves to sent value, or to error when
+//! [`Sender`](struct.Sender.html) was dropped without sending, e.g because its task was cancelled.
+//!
+//! # Examples
+//! ```
+//! use juggle::{*, dy::*, channel::{oneshot, RecvError}};
+//!
+//! let wheel = Wheel::new();
+//! let handle = wheel.handle().clone();
+//! let (tx, rx) = oneshot::channel();
+//! let (lost_tx, lost_rx) = oneshot::channel::<u32>();
+//! let worker = handle.spawn_default(async move {
+//!     let _lost_tx = lost_tx;
+//!     tx.send(42).unwrap();
+//!     loop { yield_once!(); }
+//! }).unwrap();
+//! let h = handle.clone();
+//! handle.spawn_default(async move {
+//!     assert_eq!(rx.await, Ok(42));
+//!     h.cancel(worker);
+//!     assert_eq!(lost_rx.await, Err(RecvError)); // sender dropped with cancelled task
+//! }).unwrap();
+//! smol::block_on(wheel).unwrap();
+//! ```
+
+use alloc::rc::Rc;
+use core::cell::RefCell;
+use core::fmt::{Debug, Formatter};
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+use crate::channel::{RecvError, TryRecvError};
+use crate::budget::poll_with_budget;
+
+struct Shared<T> {
+    value: Option<T>,
+    sender_dropped: bool,
+    receiver_dropped: bool,
+    receiver: Option<Waker>,
+    sender: Option<Waker>, //waiting for receiver to be dropped
+}
+
+/// Create channel for sending single value.
+pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
+    let shared = Rc::new(RefCell::new(Shared {
+        value: None,
+        sender_dropped: false,
+        receiver_dropped: false,
+        receiver: None,
+        sender: None,
+    }));
+    (Sender { shared: shared.clone() }, Receiver { shared })
+}
+
+/// Sending side of [`oneshot`](index.html) channel.
+pub struct Sender<T> {
+    shared: Rc<RefCell<Shared<T>>>,
+}
+
+impl<T> Sender<T> {
+    /// Send value, consuming sender. Returns given value back if receiver was dropped.
+    pub fn send(self, value: T) -> Result<(), T> {
+        let mut shared = self.shared.borrow_mut();
+        if shared.receiver_dropped {
+            return Err(value);
+        }
+        shared.value = Some(value);
+        drop(shared);
+        Ok(()) //receiver is woken when sender is dropped
+    }
+    /// Returns true if receiver was dropped.
+    pub fn is_closed(&self) -> bool { self.shared.borrow().receiver_dropped }
+    /// Wait until receiver is dropped, so that value doesn't have to be computed.
+    pub fn closed(&mut self) -> Closed<'_, T> { Closed { sender: self } }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut shared = self.shared.borrow_mut();
+        shared.sender_dropped = true;
+        let waker = shared.receiver.take();
+        drop(shared);
+        if let Some(waker) = waker { waker.wake(); }
+    }
+}
+
+impl<T> Debug for Sender<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Sender").field("closed", &self.is_closed()).finish()
+    }
+}
+
+/// Future returned by [`Sender::closed`](struct.Sender.html#method.closed).
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Closed<'a, T> {
+    sender: &'a mut Sender<T>,
+}
+
+impl<T> Future for Closed<'_, T> {
+    type Output = ();
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut shared = self.sender.shared.borrow_mut();
+        if shared.receiver_dropped {
+            return Poll::Ready(());
+        }
+        shared.sender = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl<T> Debug for Closed<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result { f.write_str("Closed") }
+}
+
+/// Receiving side of [`oneshot`](index.html) channel. It's a future that resolves to sent value
+/// or to error when sender was dropped without sending it.
+pub struct Receiver<T> {
+    shared: Rc<RefCell<Shared<T>>>,
+}
+
+impl<T> Receiver<T> {
+    /// Take value if it was sent, without waiting.
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        let mut shared = self.shared.borrow_mut();
+        match shared.value.take() {
+            Some(value) => Ok(value),
+            None if shared.sender_dropped => Err(TryRecvError::Closed),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+    /// Returns true if sender was dropped, value might still be waiting in channel.
+    pub fn is_closed(&self) -> bool { self.shared.borrow().sender_dropped }
+}
+
+impl<T> Future for Receiver<T> {
+    type Output = Result<T, RecvError>;
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        poll_with_budget(cx, move |cx| {
+            match self.try_recv() {
+                Ok(value) => Poll::Ready(Ok(value)),
+                Err(TryRecvError::Closed) => Poll::Ready(Err(RecvError)),
+                Err(TryRecvError::Empty) => {
+                    self.shared.borrow_mut().receiver = Some(cx.waker().clone());
+                    Poll::Pending
+                }
+            }
+        })
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let mut shared = self.shared.borrow_mut();
+        shared.receiver_dropped = true;
+        let value = shared.value.take();
+        let waker = shared.sender.take();
+        drop(shared);
+        drop(value); //value is dropped outside of borrow
+        if let Some(waker) = waker { waker.wake(); }
+    }
+}
+
+impl<T> Debug for Receiver<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Receiver").field("closed", &self.is_closed()).finish()
+    }
+}
@@ -0,0 +1,208 @@
+//! Channel that keeps single value, receivers observe its latest version.
+//!
+//! Receivers can read current value at any time with [`Receiver::borrow`](struct.Receiver.html#method.borrow)
+//! and wait until it changes with [`Receiver::changed`](struct.Receiver.html#method.changed).
+//! Intermediate values that were replaced before receiver looked at them are not observed.
+//!
+//! # Examples
+//! ```
+//! use juggle::{*, dy::*, channel::watch};
+//!
+//! let wheel = Wheel::new();
+//! let (tx, mut rx) = watch::channel("starting");
+//! wheel.handle().spawn_default(async move {
+//!     let mut seen = vec![*rx.borrow()];
+//!     while rx.changed().await.is_ok() { // error when sender was dropped
+//!         seen.push(*rx.borrow());
+//!     }
+//!     assert_eq!(seen, vec!["starting", "running", "stopped"]);
+//! }).unwrap();
+//! wheel.handle().spawn_default(async move {
+//!     yield_once!();
+//!     tx.send("running").unwrap();
+//!     yield_once!();
+//!     tx.send("stopped").unwrap();
+//! }).unwrap();
+//! smol::block_on(wheel).unwrap();
+//! ```
+
+use alloc::rc::Rc;
+use core::cell::{Ref, RefCell};
+use core::fmt::{Debug, Formatter};
+use core::future::Future;
+use core::mem;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use crate::channel::{wake_all, RecvError, SendError, WakerList};
+use crate::budget::poll_with_budget;
+
+struct State {
+    version: u64,
+    sender_dropped: bool,
+    receivers: usize,
+    waiting: WakerList, //receivers waiting for change
+}
+
+struct Shared<T> {
+    value: RefCell<T>, //borrowed separately so that it can be read while state changes
+    state: RefCell<State>,
+}
+
+/// Create channel with given initial value.
+pub fn channel<T>(init: T) -> (Sender<T>, Receiver<T>) {
+    let shared = Rc::new(Shared {
+        value: RefCell::new(init),
+        state: RefCell::new(State { version: 0, sender_dropped: false, receivers: 1, waiting: WakerList::new() }),
+    });
+    (Sender { shared: shared.clone() }, Receiver { shared, seen: 0 })
+}
+
+/// Sending side of [`watch`](index.html) channel.
+pub struct Sender<T> {
+    shared: Rc<Shared<T>>,
+}
+
+impl<T> Sender<T> {
+    /// Replace value and notify receivers. Returns given value back if there are no receivers.
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        if self.shared.state.borrow().receivers == 0 {
+            return Err(SendError(value));
+        }
+        drop(self.send_replace(value));
+        Ok(())
+    }
+    /// Replace value and notify receivers even if there are none, returns previous value.
+    ///
+    /// # Panics
+    /// Panics if value is currently borrowed.
+    pub fn send_replace(&self, value: T) -> T {
+        let old = mem::replace(&mut *self.shared.value.borrow_mut(), value);
+        self.notify();
+        old
+    }
+    /// Modify value in place and notify receivers.
+    ///
+    /// # Panics
+    /// Panics if value is currently borrowed.
+    pub fn send_modify(&self, func: impl FnOnce(&mut T)) {
+        func(&mut *self.shared.value.borrow_mut());
+        self.notify();
+    }
+    /// Returns reference to current value. Value can't be sent while it's borrowed.
+    pub fn borrow(&self) -> Ref<'_, T> { self.shared.value.borrow() }
+    /// Create new receiver, current value is treated as already seen by it.
+    pub fn subscribe(&self) -> Receiver<T> {
+        let mut state = self.shared.state.borrow_mut();
+        state.receivers += 1;
+        let seen = state.version;
+        drop(state);
+        Receiver { shared: self.shared.clone(), seen }
+    }
+    /// Returns number of receivers.
+    pub fn receiver_count(&self) -> usize { self.shared.state.borrow().receivers }
+    fn notify(&self) {
+        let mut state = self.shared.state.borrow_mut();
+        state.version += 1;
+        let wakers = state.waiting.take();
+        drop(state);
+        wake_all(wakers);
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut state = self.shared.state.borrow_mut();
+        state.sender_dropped = true;
+        let wakers = state.waiting.take();
+        drop(state);
+        wake_all(wakers);
+    }
+}
+
+impl<T> Debug for Sender<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Sender").field("receivers", &self.receiver_count()).finish()
+    }
+}
+
+/// Receiving side of [`watch`](index.html) channel, can be cloned.
+pub struct Receiver<T> {
+    shared: Rc<Shared<T>>,
+    seen: u64, //version of value that was last observed
+}
+
+impl<T> Receiver<T> {
+    /// Returns reference to current value without marking it as seen. Value can't be sent while
+    /// it's borrowed.
+    pub fn borrow(&self) -> Ref<'_, T> { self.shared.value.borrow() }
+    /// Returns reference to current value and marks it as seen.
+    pub fn borrow_and_update(&mut self) -> Ref<'_, T> {
+        self.seen = self.shared.state.borrow().version;
+        self.shared.value.borrow()
+    }
+    /// Returns true if value changed since it was last seen, or error if sender was dropped.
+    pub fn has_changed(&self) -> Result<bool, RecvError> {
+        let state = self.shared.state.borrow();
+        if state.sender_dropped { Err(RecvError) } else { Ok(state.version != self.seen) }
+    }
+    /// Wait until value changes and mark it as seen. Returned future resolves immediately if
+    /// value changed since it was last seen, or to error when sender was dropped.
+    pub fn changed(&mut self) -> Changed<'_, T> { Changed { receiver: self, key: None } }
+}
+
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Self {
+        self.shared.state.borrow_mut().receivers += 1;
+        Self { shared: self.shared.clone(), seen: self.seen }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) { self.shared.state.borrow_mut().receivers -= 1; }
+}
+
+impl<T> Debug for Receiver<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Receiver").field("changed", &self.has_changed()).finish()
+    }
+}
+
+/// Future returned by [`Receiver::changed`](struct.Receiver.html#method.changed).
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Changed<'a, T> {
+    receiver: &'a mut Receiver<T>,
+    key: Option<usize>, //registration in list of waiting receivers
+}
+
+impl<T> Future for Changed<'_, T> {
+    type Output = Result<(), RecvError>;
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        poll_with_budget(cx, move |cx| {
+            let this = &mut *self;
+            let mut state = this.receiver.shared.state.borrow_mut();
+            if state.version != this.receiver.seen {
+                let version = state.version;
+                drop(state);
+                this.receiver.seen = version;
+                return Poll::Ready(Ok(()));
+            }
+            if state.sender_dropped {
+                return Poll::Ready(Err(RecvError));
+            }
+            state.waiting.register(&mut this.key, cx.waker());
+            Poll::Pending
+        })
+    }
+}
+
+impl<T> Drop for Changed<'_, T> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key {
+            self.receiver.shared.state.borrow_mut().waiting.remove(key);
+        }
+    }
+}
+
+impl<T> Debug for Changed<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result { f.write_str("Changed") }
+}
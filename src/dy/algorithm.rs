@@ -1,119 +1,207 @@
-use alloc::collections::VecDeque;
+use alloc::boxed::Box;
+use alloc::borrow::Cow;
+use alloc::collections::{BTreeMap, VecDeque};
+use core::any::Any;
+use alloc::rc::{Rc, Weak};
 use alloc::sync::Arc;
 use alloc::vec::Vec;
 use core::cell::Cell;
-use core::fmt::{Debug, Formatter};
+use core::fmt::Formatter;
 use core::fmt::Result;
-use core::mem::swap;
-use core::task::{Context, Poll, Waker};
-use crate::dy::dyn_future::{DynamicFuture, TaskName};
-use crate::dy::handle::State;
-use crate::dy::registry::Registry;
-use crate::utils::Ucw;
-use crate::utils::AtomicWakerRegistry;
+use core::task::{Context, Poll};
+use core::time::Duration;
+use crate::dy::dyn_future::TaskName;
+use crate::dy::handle::{ErrorPolicy, IdNum, SpawnParams, State, TaskData};
+use crate::dy::deadline::Deadline;
+use crate::dy::policy::{PolicyContext, SchedulingPolicy, TaskQuery, UnorderedPolicy};
+use crate::dy::snapshot::{TaskInfo, WheelSnapshot};
+use crate::dy::history::{ExitHistory, ExitReason, ExitStatus};
+use crate::budget::with_budget;
+use crate::utils::{AtomicWakerRegistry, ContextMap, DropGuard, Ucw};
+use crate::dy::idle::{IdleSlot, IdleWaiters};
 use crate::dy::stat::{TaskWrapper, StopReason, TaskRegistry};
 
 pub(crate) type TaskKey = usize;
 
-pub(crate) struct SchedulerAlgorithm<'futures> {
-    runnable: (Ucw<VecDeque<TaskKey>>, Ucw<VecDeque<TaskKey>>),
-    which_buffer: Cell<bool>,
-    ctrl: Control<'futures>,
+// error of fallible task, stored by task right before it finishes
+pub(crate) type FailureSlot = Ucw<Option<(ErrorPolicy, Box<dyn Any>)>>;
+
+// result of polling wheel to the end
+pub(crate) enum Outcome {
+    Finished,
+    Suspended,
+    Failed(Box<dyn Any>),
+    Exited(Box<dyn Any>),
 }
 
-struct Control<'futures> {
-    registry: Registry<'futures>,
+pub(crate) struct SchedulerAlgorithm<R: TaskRegistry<TaskKey>> where R::Task: TaskWrapper {
+    registry: R,
+    policy: Ucw<Box<dyn SchedulingPolicy>>,
     last_waker: Arc<AtomicWakerRegistry>,
     current: Cell<Option<TaskKey>>,
+    switch_hint: Cell<Option<TaskKey>>, //task that should be polled right after current one
+    switched: Ucw<Vec<TaskKey>>, //tasks polled by switch hint in current beat, skipped by policy
     suspended_count: Cell<usize>,
-    deferred: Ucw<Vec<TaskKey>>,
-    scan_registry: Cell<bool>,
+    cancelled: Ucw<Vec<TaskKey>>, //tasks to remove at the beginning of next beat
+    restarting: Ucw<Vec<TaskKey>>, //tasks to restart after currently polled task yields
+    in_beat: Cell<bool>,
+    beat_polled: Cell<bool>, //true if any task was polled in current beat
+    groups: Ucw<BTreeMap<TaskKey, u32>>,
+    errors: Ucw<VecDeque<(IdNum, Box<dyn Any>)>>, //bounded, oldest are discarded first
+    error_capacity: Cell<usize>,
+    dropped_errors: Cell<usize>, //errors discarded because they didn't fit
+    stop: Ucw<Option<Outcome>>, //reason of stopping the wheel (failure or exit)
+    failure: Rc<FailureSlot>,
+    idle: IdleWaiters,
+    history: Ucw<ExitHistory>,
+    beat: Cell<u64>, //number of current beat
+    context: ContextMap,
+    removal_hooks: Ucw<Vec<Weak<dyn Fn(IdNum)>>>, //called when task is removed, before its id is reused
 }
 
-#[repr(u8)]
-enum Rotate { Wait, Continue }
-
-
-impl<'futures> SchedulerAlgorithm<'futures> {
-    pub(crate) fn new() -> Self {
+impl<R: TaskRegistry<TaskKey> + Default> SchedulerAlgorithm<R> where R::Task: TaskWrapper {
+    pub(crate) fn new() -> Self { Self::with(R::default(), Box::new(UnorderedPolicy::new())) }
+}
+impl<R: TaskRegistry<TaskKey>> SchedulerAlgorithm<R> where R::Task: TaskWrapper {
+    pub(crate) fn with(registry: R, policy: Box<dyn SchedulingPolicy>) -> Self {
         Self {
-            runnable: (Ucw::new(VecDeque::new()), Ucw::new(VecDeque::new())),
-            which_buffer: Cell::new(false),
-            ctrl: Control {
-                registry: Registry::new(),
-                last_waker: Arc::new(AtomicWakerRegistry::empty()),
-                deferred: Ucw::new(Vec::new()),
-                suspended_count: Cell::new(0),
-                current: Cell::new(None),
-                scan_registry: Cell::new(false),
-            },
-        }
-    }
-    pub(crate) fn get_current(&self) -> Option<TaskKey> { self.ctrl.current.get() }
-    //safe to call from inside task
-    fn enqueue_runnable(&self, key: TaskKey, check_absent: bool) {
-        if self.which_buffer.get() { //if now 1 is executed then add to 0 and vice versa.
-            if check_absent && self.runnable.0.borrow().contains(&key) { return; }
-            self.runnable.0.borrow_mut().push_back(key);
-        } else {
-            if check_absent && self.runnable.1.borrow().contains(&key) { return; }
-            self.runnable.1.borrow_mut().push_back(key);
+            registry,
+            policy: Ucw::new(policy),
+            last_waker: Arc::new(AtomicWakerRegistry::empty()),
+            suspended_count: Cell::new(0),
+            current: Cell::new(None),
+            switch_hint: Cell::new(None),
+            switched: Ucw::new(Vec::new()),
+            cancelled: Ucw::new(Vec::new()),
+            restarting: Ucw::new(Vec::new()),
+            in_beat: Cell::new(false),
+            beat_polled: Cell::new(false),
+            groups: Ucw::new(BTreeMap::new()),
+            errors: Ucw::new(VecDeque::new()),
+            error_capacity: Cell::new(Self::DEFAULT_ERROR_CAPACITY),
+            dropped_errors: Cell::new(0),
+            stop: Ucw::new(None),
+            failure: Rc::new(Ucw::new(None)),
+            idle: IdleWaiters::new(),
+            history: Ucw::new(ExitHistory::new(ExitHistory::DEFAULT_CAPACITY)),
+            beat: Cell::new(0),
+            context: ContextMap::new(),
+            removal_hooks: Ucw::new(Vec::new()),
         }
     }
-    pub(crate) fn clone_registry(&self) -> Arc<AtomicWakerRegistry> { self.ctrl.last_waker.clone() }
-    //safe to call from inside task because chunkslab never moves futures.
-    pub(crate) fn register(&self, dynamic: DynamicFuture<'futures>) -> Option<TaskKey> {
+    pub(crate) fn get_current(&self) -> Option<TaskKey> { self.current.get() }
+    pub(crate) const DEFAULT_ERROR_CAPACITY: usize = 64;
+
+    pub(crate) fn clone_registry(&self) -> Arc<AtomicWakerRegistry> { self.last_waker.clone() }
+    fn inc_suspended(&self) { self.suspended_count.set(self.suspended_count.get() + 1) }
+    fn dec_suspended(&self) { self.suspended_count.set(self.suspended_count.get() - 1) }
+    pub(crate) fn register(&self, dynamic: R::Task, params: &SpawnParams) -> Option<TaskKey> {
         let suspended = dynamic.get_stop_reason() == StopReason::Suspended;
-        let key = self.ctrl.registry.insert(dynamic); //won't realloc other futures because it uses ChunkSlab
+        let key = self.registry.insert(dynamic); //won't realloc other futures because it uses ChunkSlab
         if let Some(key) = key {
             if suspended {
                 //increase count cause added task was suspended
-                self.ctrl.inc_suspended();
-            } else {
-                self.enqueue_runnable(key, false); //first ever enqueue of this task
+                self.inc_suspended();
+            }
+            self.policy.borrow_mut().insert(IdNum::from_usize(key), params);
+            if let Some(group) = params.get_group() {
+                self.groups.borrow_mut().insert(key, group);
             }
         }
         key
     }
+
     //safe to call from inside task
     pub(crate) fn resume(&self, key: TaskKey) -> bool {
-        match self.ctrl.registry.get(key) {
+        match self.registry.get(key) {
             Some(task) if task.get_stop_reason() == StopReason::Suspended => {
                 task.set_stop_reason(StopReason::None);
-                self.ctrl.dec_suspended();
-
-                if task.is_runnable() || self.ctrl.current.get() == Some(key) {
-                    // Check if absent is needed cause some task might spam suspend-resume
-                    // which will otherwise cause multiple enqueues.
-                    self.enqueue_runnable(key, true);
-                } else {//task is still waiting for sth, defer it then
-                    // Check if absent as above.
-                    let mut deferred = self.ctrl.deferred.borrow_mut();
-                    if !deferred.contains(&key) {
-                        deferred.push(key);
-                    }
-                }
+                self.dec_suspended();
                 true
             }
             _ => false,
         }
     }
 
-    //if rotate_once encounters suspended task, then it will be removed from queue
+    //same as resume but also passes value to task, which can be obtained with 'take_resume_value'
+    pub(crate) fn resume_with(&self, key: TaskKey, value: Box<dyn Any>) -> bool {
+        let retired = match self.registry.get(key) {
+            Some(task) if task.get_stop_reason() == StopReason::Suspended => task.replace_resume_value(Some(value)),
+            _ => return false,
+        };
+        drop(retired); //drop previous value outside of registry borrow
+        self.resume(key)
+    }
+    pub(crate) fn take_resume_value(&self, key: TaskKey) -> Option<Box<dyn Any>> {
+        self.registry.get(key).and_then(|task| task.replace_resume_value(None))
+    }
+
+    //if beat_once encounters suspended task, then it will be skipped
     pub(crate) fn suspend(&self, key: TaskKey) -> bool {
-        match self.ctrl.registry.get(key) {
+        match self.registry.get(key) {
             Some(task) if task.get_stop_reason() == StopReason::None => {
                 task.set_stop_reason(StopReason::Suspended);
-                self.ctrl.inc_suspended();
-                //optimistic check, if is runnable then for sure will be removed from deferred
-                //in next iteration
-                if !task.is_runnable() {
-                    let mut deferred = self.ctrl.deferred.borrow_mut();
-                    if let Some(pos) = deferred.iter().position(|x| *x == key) {
-                        deferred.remove(pos);
+                self.inc_suspended();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    //safe to call from inside task, restart is deferred until no task is polled
+    pub(crate) fn restart(&self, key: TaskKey) -> bool {
+        match self.registry.get(key) {
+            Some(task) if task.is_restartable() => {
+                match task.get_stop_reason() {
+                    StopReason::Cancelled | StopReason::Cancelling => return false,
+                    StopReason::Suspended => {
+                        task.set_stop_reason(StopReason::None);
+                        self.dec_suspended();
                     }
+                    StopReason::None => {}
                 }
+                let mut restarting = self.restarting.borrow_mut();
+                if !restarting.contains(&key) {
+                    restarting.push(key);
+                }
+                drop(restarting);
+                self.last_waker.notify_wake(); //wheel might be waiting for any task to wake
+                true
+            }
+            _ => false,
+        }
+    }
 
+    fn apply_restarts(&self) {
+        loop {
+            let key = match self.restarting.borrow_mut().pop() {
+                Some(key) => key,
+                None => break,
+            };
+            let task = match self.registry.get(key) {
+                Some(task) if task.get_stop_reason() != StopReason::Cancelled => task,
+                _ => continue,
+            };
+            let retired = task.restart();
+            drop(task);
+            drop(retired); //destructor of previous future can use handle
+        }
+    }
+
+    pub(crate) fn set_cleanup(&self, key: TaskKey, cleanup: <R::Task as TaskWrapper>::Retired) -> bool {
+        let result = match self.registry.get(key) {
+            Some(task) => task.set_cleanup(cleanup),
+            None => Err(cleanup),
+        };
+        result.is_ok() //previous or rejected cleanup is dropped outside of registry borrow
+    }
+
+    // make given task next polled one, returns false if it can't be polled
+    pub(crate) fn switch_to(&self, key: TaskKey) -> bool {
+        match self.registry.get(key) {
+            Some(task) if task.get_stop_reason().is_poll_allowed() && self.current.get() != Some(key) => {
+                task.set_woken(); //switching to task wakes it, so it's polled like any woken task
+                self.switch_hint.set(Some(key));
                 true
             }
             _ => false,
@@ -121,263 +209,471 @@ impl<'futures> SchedulerAlgorithm<'futures> {
     }
 
     pub(crate) fn get_state(&self, key: TaskKey) -> State {
-        match self.ctrl.registry.get(key) {
-            Some(task) => match task.get_stop_reason() {
-                StopReason::Cancelled => State::Cancelled,
-                StopReason::Suspended => State::Suspended,
-                _ => {
-                    if task.is_runnable() { State::Runnable }
-                    else { State::Waiting }
-                }
-            }
+        match self.registry.get(key) {
+            Some(task) => self.state_of(key, &task),
             None => State::Inactive,
         }
     }
 
-    //if rotate_once encounters cancelled task, then it will be removed from queue and registry
+    fn state_of(&self, key: TaskKey, task: &R::Task) -> State {
+        match task.get_stop_reason() {
+            StopReason::Cancelled => State::Cancelled,
+            StopReason::Cancelling => State::Cancelling,
+            StopReason::Suspended => State::Suspended,
+            StopReason::None => {
+                //currently executing task has its runnable flag cleared
+                if task.is_runnable() || self.current.get() == Some(key) { State::Runnable }
+                else { State::Waiting }
+            }
+        }
+    }
+
+    // remove suspended task that can be moved to other wheel, it's not recorded in exit history.
+    // Not allowed while any task is polled, because registry entry of that task is borrowed.
+    pub(crate) fn detach(&self, key: TaskKey) -> Option<R::Task> {
+        if self.current.get().is_some() {
+            return None;
+        }
+        match self.registry.get(key) {
+            Some(task) if task.get_stop_reason() == StopReason::Suspended && task.is_portable() => {}
+            _ => return None,
+        }
+        self.dec_suspended();
+        Some(self.unregister(key))
+    }
+
+    //cancelled tasks are removed from registry at the beginning of next beat
     pub(crate) fn cancel(&self, key: TaskKey) -> bool {
-        match self.ctrl.registry.get(key) {
-            Some(task) => {
-                let r = task.get_stop_reason();
-                if r != StopReason::Cancelled {
-                    task.set_stop_reason(StopReason::Cancelled);
-                    if r == StopReason::Suspended {
-                        self.ctrl.dec_suspended();
-                        self.ctrl.scan_registry.set(true);
-                    }
-                    true
-                } else { false }
+        if let Some(task) = self.registry.get(key) {
+            let r = task.get_stop_reason();
+            if r != StopReason::Cancelled && r != StopReason::Cancelling {
+                task.set_stop_reason(StopReason::Cancelled);
+                if r == StopReason::Suspended {
+                    self.dec_suspended();
+                }
+                self.cancelled.borrow_mut().push(key);
+                return true;
+            }
+        }
+        false
+    }
+
+    // cancel task without running its cleanup, also interrupts cleanup that is already running
+    fn abort(&self, key: TaskKey) {
+        if let Some(task) = self.registry.get(key) {
+            if task.get_stop_reason() == StopReason::Cancelling {
+                task.set_stop_reason(StopReason::Cancelled);
+                self.cancelled.borrow_mut().push(key);
             }
-            _ => false,
         }
+        self.cancel(key);
     }
+
     pub(crate) fn get_by_name(&self, name: &str) -> Option<TaskKey> {
-        for (k, v) in self.ctrl.registry.iter() {
-            match v.get_name().as_str() {
-                Some(n) if n == name => return Some(k),
+        for k in 0..self.registry.capacity() {
+            match self.registry.get(k) {
+                Some(v) => match v.get_name().as_str() {
+                    Some(n) if n == name => return Some(k),
+                    _ => {}
+                }
                 _ => {}
             }
         }
         None
     }
-    pub(crate) fn registered_count(&self)->usize{ self.ctrl.registry.count() }
 
-    pub fn with_name<F, T>(&self, id: TaskKey, func: F) -> T where F: FnOnce(&TaskName) -> T {
-        match self.ctrl.registry.get(id) {
-            Some(task) => func(task.get_name()),
-            None => func(&TaskName::None),
-        }
+    pub(crate) fn registered_count(&self)->usize{ self.registry.count() }
+
+    pub(crate) fn set_deadline(&self, key: TaskKey, deadline: Deadline) -> bool {
+        self.registry.get(key).is_some() && self.policy.borrow_mut().set_deadline(IdNum::from_usize(key), deadline)
+    }
+    pub(crate) fn set_weight(&self, key: TaskKey, weight: u16) -> bool {
+        self.registry.get(key).is_some() && self.policy.borrow_mut().set_weight(IdNum::from_usize(key), weight)
+    }
+    pub(crate) fn missed_deadlines(&self, key: TaskKey) -> Option<usize> {
+        self.registry.get(key)?;
+        self.policy.borrow().missed_deadlines(IdNum::from_usize(key))
     }
 
-    pub(crate) fn format_internal(&self, f: &mut Formatter<'_>, name: &str) -> Result {
-        pub(crate) struct DebugTask<'a, 'b>(
-            &'a Registry<'b>,
-            Option<TaskKey>,
-        );
-
-        impl<'a, 'b> Debug for DebugTask<'a, 'b> {
-            fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-                match self.1 {
-                    Some(id) => {
-                        if let Some(task) = self.0.get(id) {
-                            return match task.get_name().as_str() {
-                                Some(s) => write!(f, "0x{:X}:{}", id, s),
-                                None => write!(f, "0x{:X}", id),
-                            };
-                        }
+    pub(crate) fn poll_idle(&self, slot: &mut Option<IdleSlot>, cx: &mut Context<'_>) -> Poll<()> {
+        self.idle.poll_wait(slot, cx)
+    }
+
+    pub(crate) fn exit_status(&self, id: IdNum) -> Option<ExitStatus> { self.history.borrow().get(id).cloned() }
+    pub(crate) fn recent_exits(&self) -> Vec<ExitStatus> { self.history.borrow().to_vec() }
+    pub(crate) fn set_history_capacity(&self, capacity: usize) { self.history.borrow_mut().set_capacity(capacity) }
+    pub(crate) fn set_history_clock(&self, clock: Box<dyn Fn() -> Duration>) { self.history.borrow_mut().set_clock(clock) }
+
+    fn record_exit(&self, key: TaskKey, task: &R::Task, reason: ExitReason) {
+        if !self.history.borrow().is_enabled() { return; }
+        let status = ExitStatus {
+            id: IdNum::from_usize(key),
+            name: task.get_name().to_cow(),
+            reason,
+            exit_beat: self.beat.get(),
+            exit_time: self.history.borrow().elapsed(),
+        };
+        self.history.borrow_mut().push(status);
+    }
+
+    pub(crate) fn provide<C: 'static>(&self, value: C) -> Option<Rc<C>> { self.context.provide(value) }
+    pub(crate) fn context<C: 'static>(&self) -> Option<Rc<C>> { self.context.get() }
+    // hook is called until its owner drops it
+    pub(crate) fn on_remove(&self, hook: &Rc<dyn Fn(IdNum)>) { self.removal_hooks.borrow_mut().push(Rc::downgrade(hook)) }
+
+    pub(crate) fn failure_slot(&self) -> Rc<FailureSlot> { self.failure.clone() }
+
+    fn report_error(&self, key: TaskKey, policy: ErrorPolicy, error: Box<dyn Any>) {
+        match policy {
+            ErrorPolicy::Log => {}
+            ErrorPolicy::CancelGroup => {
+                let group = self.groups.borrow().get(&key).copied();
+                if let Some(group) = group {
+                    let members: Vec<_> = self.groups.borrow().iter()
+                        .filter(|&(&k, &g)| g == group && k != key).map(|(&k, _)| k).collect();
+                    for k in members {
+                        self.cancel(k);
                     }
-                    _ => {}
                 }
-                write!(f, "None")
             }
-        }
-
-        writeln!(f, "{}{{", name)?;
-        let span = 10;
-        writeln!(f, "{:>s$}: {:?}", "current", DebugTask(&self.ctrl.registry, self.ctrl.current.get()), s = span)?;
-
-        struct RunnableDebug<'a, 'b>(&'a SchedulerAlgorithm<'b>);
-        impl<'a, 'b> Debug for RunnableDebug<'a, 'b> {
-            fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
-                if self.0.runnable.0.borrow().is_empty() && self.0.runnable.1.borrow().is_empty() { write!(f, "None") } else {
-                    let mut buff0 = &self.0.runnable.0.borrow();
-                    let mut buff1 = &self.0.runnable.1.borrow();
-                    if self.0.which_buffer.get() { swap(&mut buff0, &mut buff1); }
-                    let buff0 = buff0.iter().map(|&k| DebugTask(&self.0.ctrl.registry, Some(k)));
-                    let buff1 = buff1.iter().map(|&k| DebugTask(&self.0.ctrl.registry, Some(k)));
-                    f.debug_list().entries(buff0).entries(buff1).finish()
+            ErrorPolicy::StopWheel => {
+                let mut stop = self.stop.borrow_mut();
+                if stop.is_none() {
+                    *stop = Some(Outcome::Failed(error));
+                    return;
                 }
             }
         }
-        writeln!(f, "{:>s$}: {:?}", "runnable", RunnableDebug(self), s = span)?;
+        self.store_error(IdNum::from_usize(key), error);
+    }
+    fn store_error(&self, id: IdNum, error: Box<dyn Any>) {
+        let capacity = self.error_capacity.get();
+        let mut errors = self.errors.borrow_mut();
+        let discarded = if capacity == 0 {
+            Some(error)
+        } else {
+            let oldest = if errors.len() >= capacity { errors.pop_front().map(|(_, e)| e) } else { None };
+            errors.push_back((id, error));
+            oldest
+        };
+        drop(errors);
+        if discarded.is_some() {
+            self.dropped_errors.set(self.dropped_errors.get() + 1);
+        }
+        drop(discarded); //error is dropped outside of borrow
+    }
+    pub(crate) fn take_errors(&self) -> Vec<(IdNum, Box<dyn Any>)> {
+        core::mem::replace(&mut *self.errors.borrow_mut(), VecDeque::new()).into()
+    }
+    pub(crate) fn set_error_capacity(&self, capacity: usize) {
+        self.error_capacity.set(capacity);
+        let mut errors = self.errors.borrow_mut();
+        let excess = errors.len().saturating_sub(capacity);
+        let discarded: Vec<_> = errors.drain(..excess).collect();
+        drop(errors);
+        self.dropped_errors.set(self.dropped_errors.get() + discarded.len());
+        drop(discarded);
+    }
+    pub(crate) fn dropped_errors(&self) -> usize { self.dropped_errors.get() }
+    fn is_stopping(&self) -> bool { self.stop.borrow().is_some() }
 
-        struct WaitingDebug<'a, 'b>(&'a SchedulerAlgorithm<'b>);
-        impl<'a, 'b> Debug for WaitingDebug<'a, 'b> {
-            fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-                let deferred = self.0.ctrl.deferred.borrow();
-                let buff = deferred.iter().map(|&k| DebugTask(&self.0.ctrl.registry, Some(k)));
-                if deferred.is_empty() { write!(f, "None") } else {
-                    f.debug_list().entries(buff).finish()
-                }
-            }
+    pub(crate) fn exit(&self, value: Box<dyn Any>) -> bool {
+        let mut stop = self.stop.borrow_mut();
+        if stop.is_some() { return false; }
+        *stop = Some(Outcome::Exited(value));
+        true
+    }
+
+    // cancel and remove all tasks, returns reason of stopping the wheel
+    fn take_stop(&self) -> Option<Outcome> {
+        let outcome = self.stop.borrow_mut().take()?;
+        for k in 0..self.registry.capacity() {
+            self.abort(k);
         }
-        writeln!(f, "{:>s$}: {:?}", "waiting", WaitingDebug(self), s = span)?;
-
-        struct SuspendedDebug<'a, 'b>(&'a SchedulerAlgorithm<'b>);
-        impl<'a, 'b> Debug for SuspendedDebug<'a, 'b> {
-            fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-                let mut buff = self.0.ctrl.registry.iter().map(|(k, _)| DebugTask(&self.0.ctrl.registry, Some(k)))
-                    .filter(|t| {
-                        match t.1.and_then(|id| t.0.get(id)) {
-                            Some(task) => task.get_stop_reason() == StopReason::Suspended,
-                            None => false,
-                        }
-                    });
-                if let Some(first) = buff.next() { f.debug_list().entry(&first).entries(buff).finish() } else { write!(f, "None") }
+        self.remove_cancelled(false);
+        self.in_beat.set(false);
+        Some(outcome)
+    }
+
+    // cancel and remove all tasks of stopped wheel, reason of stopping is kept until wheel is
+    // awaited. Returns true if wheel was stopped.
+    fn stop_tasks(&self) -> bool {
+        match self.take_stop() {
+            Some(outcome) => {
+                *self.stop.borrow_mut() = Some(outcome);
+                true
             }
+            None => false,
         }
-        writeln!(f, "{:>s$}: {:?}", "suspended", SuspendedDebug(self), s = span)?;
-        write!(f, "}}")
     }
 
-    pub(crate) fn poll_internal(&self, cx: &mut Context<'_>) -> Poll<bool> {
-        loop {
-            self.ctrl.last_waker.clear();//drop previous waker if any
-            let beat_result = if self.which_buffer.get() {
-                self.ctrl.beat_once(&self.runnable.1, &self.runnable.0, cx.waker())
-            } else {
-                self.ctrl.beat_once(&self.runnable.0, &self.runnable.1, cx.waker())
-            };
-            self.which_buffer.set(!self.which_buffer.get());
-            if let Rotate::Wait = beat_result {
-                return Poll::Pending;
-            }
-            if self.runnable.0.borrow().is_empty() && self.runnable.1.borrow().is_empty()
-                && self.ctrl.deferred.borrow().is_empty() { break; }
+    pub fn with_name<F, T>(&self, id: TaskKey, func: F) -> T where F: FnOnce(&TaskName) -> T {
+        match self.registry.get(id) {
+            Some(task) => func(&task.get_name()),
+            None => func(&TaskName::None),
         }
-        if self.ctrl.suspended_count.get() != 0 { Poll::Ready(false) } //all tasks are suspended
-        else { Poll::Ready(true) } //all tasks executed to finish
     }
-}
 
-impl Control<'_> {
-    fn inc_suspended(&self) { self.suspended_count.set(self.suspended_count.get() + 1) }
-    fn dec_suspended(&self) { self.suspended_count.set(self.suspended_count.get() - 1) }
+    pub(crate) fn set_name(&self, id: TaskKey, name: TaskName) -> bool {
+        self.registry.get(id).map_or(false, |task| task.set_name(name))
+    }
 
-    #[inline]
-    fn process_tasks(&self, from: &Ucw<VecDeque<TaskKey>>, to: &Ucw<VecDeque<TaskKey>>, waker: &Waker) -> Rotate {
-        let from = &mut from.borrow_mut();
-        let deferred = &mut self.deferred.borrow_mut();
-        if !deferred.is_empty() && !Self::drain_runnable(&self.registry, deferred, from) {
-            if from.is_empty() && to.borrow().is_empty() { //if has no work to do
+    pub(crate) fn get_data(&self, id: TaskKey) -> Option<TaskData> {
+        self.registry.get(id).and_then(|task| task.get_data().cloned())
+    }
+
+    pub(crate) fn snapshot(&self) -> WheelSnapshot {
+        let current = self.current.get();
+        let tasks = (0..self.registry.capacity()).filter_map(|k| {
+            self.registry.get(k).map(|task| TaskInfo {
+                id: IdNum::from_usize(k),
+                name: task.get_name().to_cow(),
+                state: self.state_of(k, &task),
+                tags: task.get_tags().iter().map(|&t| Cow::Borrowed(t)).collect(),
+                current: current == Some(k),
+            })
+        }).collect();
+        WheelSnapshot { current: current.map(IdNum::from_usize), tasks }
+    }
+
+    pub(crate) fn format_internal(&self, f: &mut Formatter<'_>, name: &str) -> Result {
+        self.snapshot().format(f, name)
+    }
+
+    pub(crate) fn poll_internal(&self, cx: &mut Context<'_>) -> Poll<Outcome> {
+        let waker = cx.waker();
+        self.last_waker.clear();//drop previous waker if any
+        loop {
+            let polled = self.beat_once();
+            if let Some(outcome) = self.take_stop() {
+                return Poll::Ready(outcome);
+            }
+            if !polled {
                 //no runnable task found, register waker
                 self.last_waker.register(waker);
                 //check once again if no task was woken during this time
-                if !Self::drain_runnable(&self.registry, deferred, from) {
+                if !self.beat_once() {
                     //waiting begins
-                    return Rotate::Wait;//means that future should wait for waker
+                    let cnt = self.registry.count();
+                    return if cnt == 0 || cnt == self.suspended_count.get() {
+                        self.last_waker.clear(); //waker not needed, clear before finishing
+                        //all tasks finished or all suspended
+                        Poll::Ready(if cnt == 0 { Outcome::Finished } else { Outcome::Suspended })
+                    } else { Poll::Pending } //all tasks waiting
+                }else{
+                    //if any was woken then try to deregister waker, then make one rotation
+                    self.last_waker.clear();
                 }
-                //if any was woken then try to deregister waker, then make one rotation
-                self.last_waker.clear();
             }
         }
-        Rotate::Continue
     }
-    fn beat_once(&self, from: &Ucw<VecDeque<TaskKey>>, to: &Ucw<VecDeque<TaskKey>>, waker: &Waker) -> Rotate {
-        match self.process_tasks(from, to, waker) {
-            Rotate::Wait => Rotate::Wait,//indicates that future should wait for waker now
-            Rotate::Continue => {
-                self.rotate_once(from, to);
-                Rotate::Continue //can start new iteration
+
+    fn remove_task(&self, key: TaskKey, reason: ExitReason) {
+        let task = self.unregister(key);
+        self.record_exit(key, &task, reason);
+        //drop task when scheduler state is consistent and nothing is borrowed, destructor of
+        //future can then spawn, cancel or otherwise use handle of this wheel.
+        drop(task);
+    }
+
+    // remove task from registry and all scheduler structures, returned task must be dropped after
+    // all borrows are released
+    fn unregister(&self, key: TaskKey) -> R::Task {
+        let task = self.registry.remove(key).expect("Internal Error: task not found.");
+        self.groups.borrow_mut().remove(&key);
+        self.restarting.borrow_mut().retain(|&k| k != key);
+        if self.switch_hint.get() == Some(key) {
+            self.switch_hint.set(None);
+        }
+        self.policy.borrow_mut().remove(IdNum::from_usize(key));
+        let hooks: Vec<_> = { //hook can add other hooks
+            let mut hooks = self.removal_hooks.borrow_mut();
+            hooks.retain(|hook| hook.strong_count() != 0);
+            hooks.iter().filter_map(Weak::upgrade).collect()
+        };
+        hooks.iter().for_each(|hook| hook(IdNum::from_usize(key)));
+        task
+    }
+
+    // removes cancelled tasks, or starts cleanup of ones that have it when 'epilogue' is true
+    fn remove_cancelled(&self, epilogue: bool) {
+        loop {
+            let key = match self.cancelled.borrow_mut().pop() {
+                Some(key) => key,
+                None => break,
+            };
+            let (retired, reason) = match self.registry.get(key) {
+                Some(task) => {
+                    let retired = if epilogue { task.start_epilogue() } else { None };
+                    (retired, if task.is_epilogue_expired() { ExitReason::TimedOut } else { ExitReason::Cancelled })
+                }
+                None => (None, ExitReason::Cancelled),
+            };
+            match retired {
+                Some(retired) => {
+                    self.restarting.borrow_mut().retain(|&k| k != key);
+                    drop(retired); //main future is dropped before cleanup is polled
+                }
+                None => self.remove_task(key, reason),
             }
         }
     }
 
     #[inline]
-    fn peek_front_queue(queue: &Ucw<VecDeque<TaskKey>>) -> Option<TaskKey>{
-        queue.borrow().front().copied() //separate fn to drop borrow
+    fn next_task(&self) -> Option<TaskKey> {
+        //separate fn to drop borrow of policy before polling task
+        self.policy.borrow_mut().next(&PolicyContext::new(self)).map(IdNum::to_usize)
     }
-    #[inline]
-    fn rotate_once(&self, from: &Ucw<VecDeque<TaskKey>>, to: &Ucw<VecDeque<TaskKey>>) {
-        struct Guard<'a>(&'a Cell<Option<TaskKey>>,TaskKey,&'a Ucw<VecDeque<TaskKey>>,&'a Ucw<VecDeque<TaskKey>>);
-        impl<'a> Drop for Guard<'a> {
-            fn drop(&mut self) {
-                self.0.set(None);
-                let mut from = self.2.borrow_mut();
-                match from.front() { //remove from queue only if it wasn't removed inside task
-                    Some(&key) if key == self.1 => {
-                        from.pop_front(); //regular pop, key is in the same place
-                    }
-                    _ => {
-                        //need to scan 'to' queue to find if task was re-added
-                        drop(from);
-                        let mut to = self.3.borrow_mut();
-                        if let Some(index) = to.iter().position(|&k|k == self.1){
-                            to.remove(index);
-                        }
-                    }
+
+    // polls next task chosen by policy, returns None when current beat ended and Some(true) if
+    // task was polled. Beat can be interrupted between steps and continued later.
+    fn step(&self) -> Option<bool> {
+        if !self.in_beat.get() { //beginning of new beat
+            self.in_beat.set(true);
+            self.beat_polled.set(false);
+            self.beat.set(self.beat.get() + 1);
+            self.switched.borrow_mut().clear();
+            self.remove_cancelled(true);
+            self.apply_restarts();
+        }
+        let hint = self.switch_hint.take();
+        let run_key = match hint.or_else(|| self.next_task()) {
+            Some(key) => key,
+            None => {
+                self.in_beat.set(false);
+                if !self.beat_polled.get() {
+                    self.idle.notify_idle();
                 }
+                return None;
             }
+        };
+        let run_task = match self.registry.get(run_key) {
+            Some(task) => task,
+            None => return Some(false), //policy returned unknown task
+        };
+        if !run_task.get_stop_reason().is_poll_allowed() || !run_task.is_runnable() {
+            return Some(false); // next task
+        }
+        //task polled by switch hint had its turn in this beat, even if it woke itself again
+        if hint.is_some() {
+            self.switched.borrow_mut().push(run_key);
+        } else if self.switched.borrow().contains(&run_key) {
+            return Some(false);
         }
+        let cancelling = run_task.get_stop_reason() == StopReason::Cancelling;
+        self.current.set(Some(run_key));
+        let guard = DropGuard::new(||self.current.set(None));
+        // be careful with interior mutability types here cause 'poll_local' can invoke any method
+        // on handle, registry is now in borrowed state so nothing can be 'remove'd from it.
+        self.beat_polled.set(true);
+        let unwinding = DropGuard::new(|| self.record_exit(run_key, &run_task, ExitReason::Panicked));
+        let is_ready = with_budget(|| run_task.poll_local()).is_ready(); //run user code
+        core::mem::forget(unwinding); //task didn't panic
+        drop(guard);
+        self.policy.borrow_mut().polled(IdNum::from_usize(run_key));
+        let failure = self.failure.borrow_mut().take();
+        let failed = failure.is_some();
+        if let Some((policy, error)) = failure {
+            self.report_error(run_key, policy, error);
+        }
+        let restarted = self.restarting.borrow().contains(&run_key);
+        if is_ready && !restarted && run_task.get_stop_reason() != StopReason::Cancelled { //task was finished, remove from scheduler
+            drop(run_task); //must be dropped!
+            let reason = if cancelling { ExitReason::Cancelled } //cleanup finished
+                else if failed { ExitReason::Failed } else { ExitReason::Completed };
+            self.remove_task(run_key, reason);
+        } else if cancelling && !is_ready && run_task.epilogue_tick() {
+            drop(run_task);
+            self.abort(run_key); //cleanup took too long
+        } else {
+            drop(run_task);
+        }
+        self.apply_restarts();
+        Some(true)
+    }
 
-        while let Some(run_key) = Self::peek_front_queue(from) {
-            let run_task = self.registry.get(run_key).expect("Internal Error: unknown task in queue.");
-            let reason = run_task.get_stop_reason();
-            if reason != StopReason::None {
-                if reason == StopReason::Cancelled {
-                    drop(run_task);//clear last borrow
-                    self.registry.remove(run_key).expect("Internal Error: task not found.");
-                }
-                continue; //remove from queue
+    fn beat_once(&self) -> bool {
+        let mut any_poll = false;
+        while !self.is_stopping() {
+            match self.step() {
+                Some(polled) => any_poll |= polled,
+                None => break,
             }
+        }
+        any_poll
+    }
 
-            self.current.set(Some(run_key));
-            let guard = Guard(&self.current,run_key,from,to);
-            // be careful with interior mutability types here cause 'poll_local' can invoke any method
-            // on handle, 'from' queue shouldn't be edited by handles (this is not enforced) and
-            // registry is now in borrowed state so nothing can be 'remove'd from it.
-            let is_ready = run_task.poll_local().is_ready(); //run user code
-            drop(guard);
-
-            if is_ready || run_task.get_stop_reason() == StopReason::Cancelled { //task was finished or cancelled, remove from scheduler
-                drop(run_task); //must be dropped!
-                self.registry.remove(run_key).expect("Internal Error: task not found.");
-            } else if run_task.get_stop_reason() != StopReason::Suspended { //reconsider enqueuing this future again
-                if run_task.is_runnable() { //if immediately became runnable then enqueue it
-                    to.borrow_mut().push_back(run_key);
-                } else { //put it on deferred queue
-                    self.deferred.borrow_mut().push(run_key);
+    // poll given future between beats until it's ready
+    pub(crate) fn poll_until<T>(&self, cx: &mut Context<'_>, mut future: impl FnMut(&mut Context<'_>) -> Poll<T>) -> Poll<T> {
+        assert!(self.current.get().is_none(), "Cannot run wheel inside its own task.");
+        loop {
+            //after exit or failure only given future is polled, tasks that it waits for are dropped
+            self.stop_tasks();
+            if let Poll::Ready(value) = future(cx) {
+                return Poll::Ready(value);
+            }
+            if !self.beat_once() {
+                //no task was polled, wait until future or any task is woken
+                self.last_waker.register(cx.waker());
+                if !self.beat_once() {
+                    return Poll::Pending;
                 }
+                self.last_waker.clear();
             }
         }
-        if self.scan_registry.get() { //perform scan
-            self.scan_registry.set(false); //clear flag
-            //todo make this more efficient
-            //from queue is now empty
-            //but to queue must be checked if it contains any cancelled tasks
-            to.borrow_mut().retain(|&key| self.registry.get(key).unwrap().get_stop_reason() != StopReason::Cancelled);
-            //now registry can be cleared
-            self.registry.retain(|_,v| v.get_stop_reason() != StopReason::Cancelled);
-        }
-    }
-
-    fn drain_runnable(registry: &Registry<'_>,
-                      from: &mut Vec<TaskKey>, to: &mut VecDeque<TaskKey>) -> bool {
-        let prev = from.len();
-        from.retain(|&elem| {
-            let task = registry.get(elem).unwrap();
-            if task.get_stop_reason() == StopReason::Suspended { return false; }
-            if task.get_stop_reason() == StopReason::Cancelled {
-                drop(task);
-                registry.remove(elem).expect("Internal Error: task not found.");
-                return false;
+    }
+
+    // run single beat and then yield, so that wheel can be nested inside task of other wheel
+    // without blocking it. Resolves when all tasks finished/are suspended or wheel was stopped.
+    pub(crate) fn poll_beat(&self, cx: &mut Context<'_>) -> Poll<Outcome> {
+        self.last_waker.clear();
+        let polled = self.beat_once();
+        if let Some(outcome) = self.take_stop() {
+            return Poll::Ready(outcome);
+        }
+        let cnt = self.registry.count();
+        if cnt == 0 {
+            return Poll::Ready(Outcome::Finished);
+        }
+        if polled {
+            cx.waker().wake_by_ref(); //continue in next poll
+            return Poll::Pending;
+        }
+        if cnt == self.suspended_count.get() {
+            return Poll::Ready(Outcome::Suspended);
+        }
+        self.last_waker.register(cx.waker());
+        if self.has_runnable() { //task was woken in meantime
+            self.last_waker.clear();
+            cx.waker().wake_by_ref();
+        }
+        Poll::Pending
+    }
+
+    // poll tasks until 'expired' returns true or all tasks are waiting/suspended/finished,
+    // returns true if there are tasks that can still be polled, false also when wheel was stopped.
+    pub(crate) fn poll_for(&self, mut expired: impl FnMut() -> bool) -> bool {
+        loop {
+            if self.stop_tasks() {
+                return false; //wheel must be awaited to obtain result
+            }
+            if expired() {
+                return self.has_runnable();
             }
-            if task.is_runnable() {
-                to.push_back(elem);
-                return false;
+            if self.step().is_none() && !self.beat_polled.get() && !self.has_runnable() {
+                return false; //whole beat passed without polling any task (and no idle waiter was woken)
             }
-            true
-        });
-        prev != from.len()
+        }
     }
-}
\ No newline at end of file
+
+    fn has_runnable(&self) -> bool {
+        (0..self.registry.capacity()).any(|k| match self.registry.get(k) {
+            Some(task) => task.get_stop_reason().is_poll_allowed() && task.is_runnable(),
+            None => false,
+        })
+    }
+}
+
+impl<R: TaskRegistry<TaskKey>> TaskQuery for SchedulerAlgorithm<R> where R::Task: TaskWrapper {
+    fn task_state(&self, key: TaskKey) -> State { self.get_state(key) }
+}
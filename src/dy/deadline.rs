@@ -0,0 +1,186 @@
+use alloc::collections::{BTreeMap, BTreeSet};
+use core::fmt::{Debug, Formatter};
+use core::ops::Bound;
+use core::time::Duration;
+use crate::dy::{IdNum, PolicyContext, SchedulingPolicy, SpawnParams};
+use crate::utils::TimerClock;
+
+/// Point in time until which task should be polled, used by [`EdfPolicy`](struct.EdfPolicy.html).
+///
+/// Deadline can be given in [`SpawnParams`](struct.SpawnParams.html#method.with_deadline) or changed
+/// later with [`WheelHandle::set_deadline`](struct.WheelHandle.html#method.set_deadline).
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Deadline {
+    /// Deadline relative to the moment when it was assigned to task (when task was spawned or when
+    /// deadline was changed).
+    After(Duration),
+    /// Absolute deadline, measured as time elapsed since scheduling policy was created.
+    At(Duration),
+}
+
+/// Earliest-deadline-first policy that polls runnable task with the nearest deadline first.
+///
+/// In each beat every runnable task is polled at most once, in order of their deadlines. Tasks
+/// without deadline are polled after all tasks with deadline, in order of their identifiers. Task
+/// woken during beat is polled in the same beat only if its deadline is later than deadline of
+/// last polled task, otherwise it waits for next beat. Time is measured with given
+/// [`TimerClock`](../utils/trait.TimerClock.html).
+///
+/// When deadline of task passes, it's counted as missed deadline (at most once for each assigned
+/// deadline), no matter if task was runnable or waiting for some event at that time. Number of
+/// missed deadlines can be obtained with
+/// [`WheelHandle::missed_deadlines`](struct.WheelHandle.html#method.missed_deadlines).
+///
+/// # Examples
+/// ```
+/// use juggle::{*, dy::*, utils::StdTimerClock};
+/// use std::time::Duration;
+///
+/// let wheel = Wheel::with_policy(EdfPolicy::new(StdTimerClock));
+/// let handle = wheel.handle();
+/// let late = handle.spawn(SpawnParams::with_deadline(Deadline::After(Duration::from_secs(2))), async {/*...*/}).unwrap();
+/// let soon = handle.spawn(SpawnParams::with_deadline(Deadline::After(Duration::from_secs(1))), async {/*...*/}).unwrap();
+///
+/// smol::block_on(wheel).unwrap(); // task 'soon' is polled before task 'late'
+/// ```
+pub struct EdfPolicy<C: TimerClock<Duration=Duration>> where C::Instant: Clone {
+    clock: C,
+    epoch: C::Instant,
+    tasks: BTreeMap<IdNum, EdfEntry>,
+    order: BTreeSet<EdfKey>, //all tasks in order of polling
+    pending: BTreeSet<(Duration, IdNum)>, //deadlines that weren't counted as missed yet
+    cursor: Option<EdfKey>, //last task visited in current beat
+    beat: usize,
+    in_beat: bool,
+}
+
+// position of task in order of polling, tasks without deadline go last
+type EdfKey = (bool, Duration, IdNum);
+
+#[derive(Debug)]
+struct EdfEntry {
+    deadline: Option<Duration>,
+    missed: usize,
+    visited: usize, //number of last beat in which this task was polled
+}
+
+impl EdfEntry {
+    fn key(&self, id: IdNum) -> EdfKey { (self.deadline.is_none(), self.deadline.unwrap_or_default(), id) }
+}
+
+impl<C: TimerClock<Duration=Duration>> EdfPolicy<C> where C::Instant: Clone {
+    /// Create new instance of this policy that uses given clock. Absolute deadlines are measured
+    /// from the moment of calling this function.
+    pub fn new(clock: C) -> Self {
+        let epoch = clock.start();
+        Self {
+            clock, epoch, tasks: BTreeMap::new(), order: BTreeSet::new(), pending: BTreeSet::new(),
+            cursor: None, beat: 0, in_beat: false,
+        }
+    }
+    /// Time elapsed since this policy was created.
+    pub fn elapsed(&self) -> Duration { self.clock.stop(self.epoch.clone()) }
+
+    fn absolute(&self, deadline: Deadline) -> Duration {
+        match deadline {
+            Deadline::After(dur) => self.elapsed() + dur,
+            Deadline::At(dur) => dur,
+        }
+    }
+    fn add(&mut self, id: IdNum, entry: EdfEntry) {
+        self.order.insert(entry.key(id));
+        if let Some(deadline) = entry.deadline {
+            self.pending.insert((deadline, id));
+        }
+        self.tasks.insert(id, entry);
+    }
+    fn take(&mut self, id: IdNum) -> Option<EdfEntry> {
+        let entry = self.tasks.remove(&id)?;
+        self.order.remove(&entry.key(id));
+        if let Some(deadline) = entry.deadline {
+            self.pending.remove(&(deadline, id));
+        }
+        Some(entry)
+    }
+    // count all deadlines that passed, including deadlines of tasks that are not runnable
+    fn count_missed(&mut self) {
+        let now = self.elapsed();
+        while let Some(&(deadline, id)) = self.pending.iter().next() {
+            if deadline >= now {
+                break;
+            }
+            self.pending.remove(&(deadline, id));
+            self.tasks.get_mut(&id).expect("Internal Error: pending deadline of removed task.").missed += 1;
+        }
+    }
+}
+
+impl<C: TimerClock<Duration=Duration>> SchedulingPolicy for EdfPolicy<C> where C::Instant: Clone {
+    fn insert(&mut self, id: IdNum, params: &SpawnParams) {
+        let deadline = params.get_deadline().map(|d| self.absolute(d));
+        self.add(id, EdfEntry { deadline, missed: 0, visited: 0 });
+    }
+    fn remove(&mut self, id: IdNum) {
+        self.take(id);
+    }
+    fn next(&mut self, ctx: &PolicyContext<'_>) -> Option<IdNum> {
+        if !self.in_beat {
+            self.in_beat = true;
+            self.beat = self.beat.wrapping_add(1);
+            self.cursor = None;
+        }
+        self.count_missed();
+        let (beat, tasks) = (self.beat, &self.tasks);
+        let start = match self.cursor {
+            Some(key) => Bound::Excluded(key),
+            None => Bound::Unbounded,
+        };
+        //task can be visited already if its deadline was moved during this beat
+        let found = self.order.range((start, Bound::Unbounded))
+            .find(|&&(_, _, id)| tasks[&id].visited != beat && ctx.is_pollable(id))
+            .copied();
+        match found {
+            Some(key) => {
+                let id = key.2;
+                self.cursor = Some(key);
+                self.tasks.get_mut(&id).unwrap().visited = beat;
+                Some(id)
+            }
+            None => {
+                self.in_beat = false;
+                None
+            }
+        }
+    }
+    fn set_deadline(&mut self, id: IdNum, deadline: Deadline) -> bool {
+        let deadline = self.absolute(deadline);
+        match self.take(id) {
+            Some(entry) => {
+                self.add(id, EdfEntry { deadline: Some(deadline), ..entry });
+                true
+            }
+            None => false,
+        }
+    }
+    fn missed_deadlines(&self, id: IdNum) -> Option<usize> {
+        let entry = self.tasks.get(&id)?;
+        //deadline could pass since last beat, while wheel was waiting
+        let overdue = match entry.deadline {
+            Some(deadline) => self.pending.contains(&(deadline, id)) && self.elapsed() > deadline,
+            None => false,
+        };
+        Some(entry.missed + overdue as usize)
+    }
+}
+
+impl<C: TimerClock<Duration=Duration>> Debug for EdfPolicy<C> where C::Instant: Clone {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        struct DebugEntry<'a>(&'a EdfEntry);
+        impl Debug for DebugEntry<'_> {
+            fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+                f.debug_struct("").field("deadline", &self.0.deadline).field("missed", &self.0.missed).finish()
+            }
+        }
+        f.debug_map().entries(self.tasks.iter().map(|(k, v)| (k, DebugEntry(v)))).finish()
+    }
+}
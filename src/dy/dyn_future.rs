@@ -1,22 +1,44 @@
 use alloc::boxed::Box;
+use core::any::Any;
+use alloc::borrow::Cow;
+use alloc::string::ToString;
 use alloc::sync::Arc;
-use core::cell::{Cell, UnsafeCell};
+use alloc::vec::Vec;
+use core::cell::{Cell, Ref, RefCell, UnsafeCell};
 use core::future::Future;
 use core::ops::Deref;
 use core::pin::Pin;
 use core::sync::atomic::{AtomicBool, Ordering};
 use core::task::*;
-use crate::utils::{AtomicWakerRegistry, DynamicWake, to_waker, DropGuard, BorrowedWaker};
+use crate::utils::{AtomicCell, AtomicWakerRegistry, DynamicWake, to_waker, DropGuard, BorrowedWaker};
 use crate::dy::SpawnParams;
+use crate::dy::handle::{CancelHandler, PortableParams, TaskData};
 use crate::dy::stat::{TaskWrapper, StopReason};
 
+pub(crate) type BoxedFuture<'a> = Pin<Box<dyn Future<Output=()> + 'a>>;
+pub(crate) type FutureFactory<'a> = Box<dyn FnMut() -> BoxedFuture<'a> + 'a>;
+pub(crate) type SendFuture = Pin<Box<dyn Future<Output=()> + Send>>;
+
 pub(crate) struct DynamicFuture<'a> {
     //not send not sync
-    pinned_future: UnsafeCell<Pin<Box<dyn Future<Output=()> + 'a>>>,
+    pinned_future: UnsafeCell<BoxedFuture<'a>>,
+    factory: Option<UnsafeCell<FutureFactory<'a>>>, //present if task can be restarted
     flags: SyncFlags,
-    name: TaskName,
+    name: RefCell<TaskName>, //can be changed when not borrowed by 'with_name'
+    data: Option<TaskData>,
+    tags: Vec<&'static str>,
     stop_reason: Cell<StopReason>,
     polling: Cell<bool>,
+    resume_value: Cell<Option<Box<dyn Any>>>,
+    on_cancel: RefCell<Option<Cleanup<'a>>>, //taken when epilogue starts
+    epilogue_polls: Cell<usize>,
+    portable: Option<Box<PortableParams>>, //present if future is Send + 'static and data is shared, so task can be detached
+}
+
+// cleanup future of cancelled task, local one is set by handle and can borrow from wheel's scope
+pub(crate) enum Cleanup<'a> {
+    Shared(CancelHandler),
+    Local(BoxedFuture<'a>),
 }
 
 #[derive(Debug, Eq, PartialEq, Hash, Clone)]
@@ -33,26 +55,151 @@ impl TaskName {
             TaskName::None => None,
         }
     }
+    pub fn to_cow(&self)->Option<Cow<'static,str>>{
+        match self {
+            TaskName::Static(s) => Some(Cow::Borrowed(s)),
+            TaskName::Dynamic(s) => Some(Cow::Owned(s.to_string())),
+            TaskName::None => None,
+        }
+    }
 }
 
 impl<'a> DynamicFuture<'a> {
-    pub fn new(future: Pin<Box<dyn Future<Output=()> + 'a>>, global: Arc<AtomicWakerRegistry>,
-               params: SpawnParams) -> Self {
+    pub fn new(future: BoxedFuture<'a>, global: Arc<AtomicWakerRegistry>,
+               params: &SpawnParams) -> Self {
+        Self::with_factory(future, None, SyncFlags::new(global), params)
+    }
+    pub fn from_factory(mut factory: FutureFactory<'a>, global: Arc<AtomicWakerRegistry>,
+                        params: &SpawnParams) -> Self {
+        let future = factory();
+        Self::with_factory(future, Some(factory), SyncFlags::new(global), params)
+    }
+    pub fn portable(future: SendFuture, global: Arc<AtomicWakerRegistry>, params: &SpawnParams) -> Self {
+        let mut this = Self::with_factory(future, None, SyncFlags::new(global), params);
+        this.portable = PortableParams::new(params.clone()).map(Box::new);
+        this
+    }
+    // rebind task detached from other wheel to this one, it keeps its flags so that wakers
+    // cloned before detaching wake this wheel
+    pub fn attach(task: DetachedTask, global: Arc<AtomicWakerRegistry>) -> (Self, SpawnParams) {
+        let DetachedTask { future, flags, params } = task;
+        flags.rebind(global);
+        let mut this = Self::with_factory(future, None, flags, &params.0);
+        this.portable = Some(Box::new(params.clone()));
+        (this, params.0)
+    }
+    // take task apart so it can be moved to other wheel, gives task back if it's not portable
+    pub fn detach(self) -> Result<DetachedTask, Self> {
+        let mut params = match self.portable {
+            Some(ref params) if !self.has_local_cleanup() => PortableParams::clone(params),
+            _ => return Err(self),
+        };
+        params.0.suspended = true;
+        params.0.name = self.name.into_inner();
+        params.0.on_cancel = match self.on_cancel.into_inner() {
+            Some(Cleanup::Shared(handler)) => Some(handler),
+            _ => None,
+        };
+        params.0.epilogue_poll_limit = self.epilogue_polls.get();
+        let future = self.pinned_future.into_inner();
+        //SAFETY: portable task is created only from future that is Send + 'static, so this restores
+        //its original type. Both are fat pointers with the same vtable, markers don't change it.
+        let future = unsafe { core::mem::transmute::<BoxedFuture<'a>, SendFuture>(future) };
+        Ok(DetachedTask { future, flags: self.flags, params })
+    }
+    fn with_factory(future: BoxedFuture<'a>, factory: Option<FutureFactory<'a>>,
+                    flags: SyncFlags, params: &SpawnParams) -> Self {
         Self {
             pinned_future: UnsafeCell::new(future),
-            flags: SyncFlags::new(global),
-            name: params.name,
+            factory: factory.map(UnsafeCell::new),
+            flags,
+            name: RefCell::new(params.name.clone()),
+            data: params.data.clone(),
+            tags: params.tags.clone(),
             stop_reason: Cell::new(if params.suspended {StopReason::Suspended} else {StopReason::None}),
             polling: Cell::new(false),
+            resume_value: Cell::new(None),
+            on_cancel: RefCell::new(params.on_cancel.clone().map(Cleanup::Shared)),
+            epilogue_polls: Cell::new(params.epilogue_poll_limit),
+            portable: None,
         }
     }
+    fn has_local_cleanup(&self) -> bool {
+        matches!(*self.on_cancel.borrow(), Some(Cleanup::Local(_)))
+    }
 }
 
 impl<'a> TaskWrapper for DynamicFuture<'a>{
-    fn get_name(&self) -> &TaskName { &self.name }
+    fn get_name(&self) -> Ref<'_, TaskName> { self.name.borrow() }
+    fn set_name(&self, name: TaskName) -> bool {
+        match self.name.try_borrow_mut() {
+            Ok(mut current) => {
+                *current = name;
+                true
+            }
+            Err(_) => false,
+        }
+    }
+    fn get_data(&self) -> Option<&TaskData> { self.data.as_ref() }
+    fn get_tags(&self) -> &[&'static str] { &self.tags }
     fn get_stop_reason(&self) -> StopReason { self.stop_reason.get() }
     fn set_stop_reason(&self, val: StopReason) { self.stop_reason.set(val); }
     fn is_runnable(&self) -> bool { self.flags.is_runnable() }
+    fn set_woken(&self) { self.flags.set_runnable(true) }
+    type Retired = BoxedFuture<'a>;
+    fn is_restartable(&self) -> bool { self.factory.is_some() }
+    fn restart(&self) -> Option<Self::Retired> {
+        let factory = self.factory.as_ref()?;
+        //SAFETY: factory and future are guarded by the same flag as polling, so they are never
+        //borrowed twice, even if factory tries to restart this task.
+        if self.polling.replace(true) {
+            panic!("Cannot restart task while it's polled.");
+        }
+        let guard = DropGuard::new(||self.polling.set(false));
+        let future = unsafe { (&mut *factory.get())() };
+        let old = unsafe { core::mem::replace(&mut *self.pinned_future.get(), future) };
+        drop(guard);
+        self.flags.set_runnable(true);
+        Some(old)
+    }
+    fn replace_resume_value(&self, value: Option<Box<dyn Any>>) -> Option<Box<dyn Any>> {
+        self.resume_value.replace(value)
+    }
+    fn start_epilogue(&self) -> Option<Self::Retired> {
+        let cleanup = self.on_cancel.borrow_mut().take()?;
+        //SAFETY: same as in restart, future is never replaced while it's polled.
+        if self.polling.replace(true) {
+            panic!("Cannot start epilogue of task while it's polled.");
+        }
+        let guard = DropGuard::new(||self.polling.set(false));
+        let future = match cleanup {
+            Cleanup::Shared(handler) => handler.create(),
+            Cleanup::Local(future) => future,
+        };
+        let old = unsafe { core::mem::replace(&mut *self.pinned_future.get(), future) };
+        drop(guard);
+        self.stop_reason.set(StopReason::Cancelling);
+        self.flags.set_runnable(true);
+        Some(old)
+    }
+    fn epilogue_tick(&self) -> bool {
+        let left = self.epilogue_polls.get().saturating_sub(1);
+        self.epilogue_polls.set(left);
+        left == 0
+    }
+    fn is_epilogue_expired(&self) -> bool { self.epilogue_polls.get() == 0 }
+    fn set_cleanup(&self, cleanup: Self::Retired) -> Result<Option<Self::Retired>, Self::Retired> {
+        if self.stop_reason.get() == StopReason::Cancelling {
+            return Err(cleanup);
+        }
+        Ok(match self.on_cancel.replace(Some(Cleanup::Local(cleanup))) {
+            Some(Cleanup::Local(old)) => Some(old),
+            //wrap handler so that it's dropped together with retired future
+            Some(Cleanup::Shared(handler)) => Some(Box::pin(async move { drop(handler) })),
+            None => None,
+        })
+    }
+    fn is_portable(&self) -> bool { self.portable.is_some() && !self.has_local_cleanup() }
     fn poll_local(&self) -> Poll<()> {
         //SAFETY: guard against undefined behavior of borrowing UnsafeCell mutably twice.
         if self.polling.replace(true) {
@@ -89,26 +236,53 @@ struct SyncFlags {
 impl SyncFlags {
     fn new(global: Arc<AtomicWakerRegistry>) -> Self {
         let flags = Arc::new(InnerSyncFlags {
-            global,
+            global: AtomicCell::new(global),
             runnable: AtomicBool::new(true),
         });
         Self {
             flags,
         }
     }
-    fn is_runnable(&self) -> bool { self.flags.runnable.load(Ordering::Relaxed) }
+    fn is_runnable(&self) -> bool { self.flags.runnable.load(Ordering::Acquire) }
     fn set_runnable(&self, value: bool) { self.flags.runnable.store(value, Ordering::Release) }
+    fn rebind(&self, global: Arc<AtomicWakerRegistry>) { drop(self.flags.global.swap(global)) }
 }
 
 struct InnerSyncFlags {
-    global: Arc<AtomicWakerRegistry>,
+    global: AtomicCell<Arc<AtomicWakerRegistry>>, //replaced when task is moved to other wheel
     runnable: AtomicBool,
 }
 
 impl DynamicWake for InnerSyncFlags {
     fn wake(&self) {
-        //todo is it now race condition free?
-        self.global.notify_wake();
+        //flag must be set before wheel is notified, otherwise wheel woken on other thread might
+        //miss it and wait forever
         self.runnable.store(true, Ordering::Release);
+        self.global.clone_inner().notify_wake();
     }
-}
\ No newline at end of file
+}
+
+/// Suspended task taken out of [`Wheel`](struct.Wheel.html) by
+/// [`WheelHandle::detach`](struct.WheelHandle.html#method.detach).
+///
+/// It holds future of the task together with its name and parameters. It can be sent to other
+/// thread and registered in other wheel with [`WheelHandle::attach`](struct.WheelHandle.html#method.attach),
+/// task then continues from the point where it was suspended. Dropping this struct drops the task.
+pub struct DetachedTask {
+    future: SendFuture,
+    flags: SyncFlags,
+    params: PortableParams,
+}
+
+impl DetachedTask {
+    /// Returns name of detached task, or `None` if task is unnamed.
+    pub fn name(&self) -> Option<&str> { self.params.0.name.as_str() }
+    /// Returns parameters that will be used when task is attached. Task is always attached suspended.
+    pub fn params(&self) -> &SpawnParams { &self.params.0 }
+}
+
+impl core::fmt::Debug for DetachedTask {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("DetachedTask").field("params", &self.params.0).finish()
+    }
+}
@@ -0,0 +1,131 @@
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::fmt::{Debug, Formatter};
+use core::ops::Bound;
+use crate::dy::{IdNum, PolicyContext, SchedulingPolicy, SpawnParams};
+use crate::utils::{TimerClock, TimingGroup};
+
+/// Weighted fair-share policy that divides measured poll time between tasks proportionally to
+/// their weights.
+///
+/// Weight of task is given by [`SpawnParams::weight`](struct.SpawnParams.html#method.weight) and
+/// can be changed with [`WheelHandle::set_weight`](struct.WheelHandle.html#method.set_weight).
+/// Time of each poll is measured with given [`TimerClock`](../utils/trait.TimerClock.html) and
+/// accumulated in [`TimingGroup`](../utils/struct.TimingGroup.html). Tasks are visited in order of
+/// their identifiers, but runnable task that used more than its share of time is skipped in current
+/// beat. When all runnable tasks are throttled, they are polled anyway so that the wheel never
+/// stalls.
+///
+/// This policy works like wrapping each task in [`LoadBalance`](../utils/struct.LoadBalance.html),
+/// without need to wrap futures by hand.
+///
+/// # Examples
+/// ```
+/// use juggle::{*, dy::*, utils::StdTimerClock};
+///
+/// let wheel = Wheel::with_policy(FairSharePolicy::new(StdTimerClock));
+/// // task with weight 2 will get twice as much time as other task.
+/// wheel.handle().spawn(SpawnParams::default().weight(2), Yield::times(10)).unwrap();
+/// wheel.handle().spawn(SpawnParams::default(), Yield::times(10)).unwrap();
+/// smol::block_on(wheel).unwrap();
+/// ```
+pub struct FairSharePolicy<C: TimerClock> {
+    clock: C,
+    group: TimingGroup<C::Duration>,
+    tasks: BTreeMap<IdNum, usize>, //task id to group key
+    cursor: Option<IdNum>,
+    throttled: Vec<IdNum>, //tasks skipped in current beat
+    any_polled: bool,
+    measure: Option<C::Instant>,
+}
+
+impl<C: TimerClock> FairSharePolicy<C> {
+    /// Create new instance of this policy that uses given clock to measure poll time.
+    pub fn new(clock: C) -> Self {
+        Self {
+            clock,
+            group: TimingGroup::new(),
+            tasks: BTreeMap::new(),
+            cursor: None,
+            throttled: Vec::new(),
+            any_polled: false,
+            measure: None,
+        }
+    }
+
+    fn start(&mut self, id: IdNum) -> Option<IdNum> {
+        self.measure = Some(self.clock.start());
+        Some(id)
+    }
+}
+
+impl<C: TimerClock> SchedulingPolicy for FairSharePolicy<C> {
+    fn insert(&mut self, id: IdNum, params: &SpawnParams) {
+        let key = self.group.insert(params.get_weight());
+        self.tasks.insert(id, key);
+    }
+    fn remove(&mut self, id: IdNum) {
+        if let Some(key) = self.tasks.remove(&id) {
+            self.group.remove(key);
+        }
+        self.throttled.retain(|&k| k != id);
+    }
+    fn next(&mut self, ctx: &PolicyContext<'_>) -> Option<IdNum> {
+        let start = match self.cursor {
+            Some(c) => Bound::Excluded(c),
+            None => Bound::Unbounded,
+        };
+        let group = &self.group;
+        let throttled = &mut self.throttled;
+        let found = self.tasks.range((start, Bound::Unbounded))
+            .filter(|(&id, _)| ctx.is_pollable(id))
+            .find(|(&id, &key)| {
+                if group.can_execute(key) { true } else {
+                    throttled.push(id);
+                    false
+                }
+            }).map(|(&id, _)| id);
+        if let Some(id) = found {
+            self.cursor = Some(id);
+            self.any_polled = true;
+            return self.start(id);
+        }
+        self.cursor = self.tasks.keys().next_back().copied();
+        if !self.any_polled { //all runnable tasks are throttled, poll them anyway
+            while !self.throttled.is_empty() {
+                let id = self.throttled.remove(0);
+                if ctx.is_pollable(id) {
+                    return self.start(id);
+                }
+            }
+        }
+        //end of beat
+        self.cursor = None;
+        self.any_polled = false;
+        self.throttled.clear();
+        None
+    }
+    fn polled(&mut self, id: IdNum) {
+        if let Some(start) = self.measure.take() {
+            let dur = self.clock.stop(start);
+            if let Some(&key) = self.tasks.get(&id) {
+                self.group.update_duration(key, dur);
+            }
+        }
+    }
+    fn set_weight(&mut self, id: IdNum, weight: u16) -> bool {
+        match self.tasks.get(&id) {
+            Some(&key) => {
+                self.group.set_slot_count(key, weight);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl<C: TimerClock + Debug> Debug for FairSharePolicy<C> where C::Duration: Debug {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("FairSharePolicy").field("clock", &self.clock).field("group", &self.group).finish()
+    }
+}
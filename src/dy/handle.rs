@@ -1,19 +1,34 @@
 use alloc::boxed::Box;
-use alloc::rc::Weak;
-use alloc::string::{String, ToString};
+use alloc::rc::{Rc, Weak};
+use alloc::string::String;
+use alloc::vec::Vec;
 use alloc::borrow::Cow;
+use alloc::sync::Arc;
+use core::any::Any;
 use core::fmt::{Debug, Formatter};
 use core::future::Future;
 use core::hash::{Hash, Hasher};
+use core::marker::PhantomData;
+use core::num::NonZeroU16;
 use core::pin::Pin;
-use crate::dy::dyn_future::{DynamicFuture, TaskName};
-use crate::dy::Algorithm;
+use core::task::{Context, Poll};
+use core::time::Duration;
+use crate::dy::algorithm::Outcome;
+use crate::dy::dyn_future::{BoxedFuture, DetachedTask, DynamicFuture, TaskName};
+use crate::dy::{Algorithm, Deadline, SuspendError, Wheel};
+use crate::dy::snapshot::{TaskInfo, WheelSnapshot};
+use crate::dy::history::ExitStatus;
+use crate::dy::idle::IdleSlot;
+use crate::utils::TimerClock;
 
 /// Handle used to spawn and control tasks in assigned [`Wheel`](struct.Wheel.html). All tasks
 /// manipulation are done by this struct.
-#[derive(Clone)]
-pub struct WheelHandle<'futures> {
+///
+/// Type parameters `T` and `E` are output and error types of associated wheel, see
+/// [`exit`](#method.exit) and [`spawn_fallible`](#method.spawn_fallible).
+pub struct WheelHandle<'futures, T = (), E = SuspendError> {
     ptr: Weak<Algorithm<'futures>>,
+    _phantom: PhantomData<fn() -> (T, E)>,
 }
 
 /// Represents identifier of task registered by [`WheelHandle`](struct.WheelHandle.html).
@@ -21,6 +36,7 @@ pub struct WheelHandle<'futures> {
 /// Identifiers are only valid when distinguishing tasks registered inside the same
 /// [`Wheel`](struct.Wheel.html). Two different wheels can have tasks with the same identifiers.
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct IdNum(core::num::NonZeroUsize);
 
 impl IdNum {
@@ -46,10 +62,108 @@ impl Debug for IdNum {
 pub struct SpawnParams {
     pub(crate) suspended: bool,
     pub(crate) name: TaskName,
+    pub(crate) deadline: Option<Deadline>,
+    pub(crate) weight: NonZeroU16,
+    pub(crate) error_policy: ErrorPolicy,
+    pub(crate) group: Option<u32>,
+    pub(crate) on_cancel: Option<CancelHandler>,
+    pub(crate) epilogue_poll_limit: usize,
+    pub(crate) data: Option<TaskData>,
+    pub(crate) tags: Vec<&'static str>,
+}
+
+/// User data attached to task, compared and hashed by identity. Only shared data can be sent
+/// to other threads together with its task.
+#[derive(Clone)]
+pub(crate) enum TaskData {
+    Shared(Arc<dyn Any + Send + Sync>),
+    Local(Rc<dyn Any>),
+}
+
+impl TaskData {
+    pub(crate) fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        match self {
+            TaskData::Shared(data) => data.downcast_ref(),
+            TaskData::Local(data) => data.downcast_ref(),
+        }
+    }
+    fn address(&self) -> usize {
+        match self {
+            TaskData::Shared(data) => Arc::as_ptr(data) as *const u8 as usize,
+            TaskData::Local(data) => Rc::as_ptr(data) as *const u8 as usize,
+        }
+    }
+}
+impl PartialEq for TaskData {
+    fn eq(&self, other: &Self) -> bool { self.address() == other.address() }
+}
+impl Eq for TaskData {}
+impl Hash for TaskData {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.address()); // identity hash code
+    }
+}
+
+/// Spawn parameters without local data, they can be sent to other threads.
+#[derive(Clone)]
+pub(crate) struct PortableParams(pub(crate) SpawnParams); //data is never TaskData::Local
+
+//SAFETY: local data is the only part of SpawnParams that is not Send and Sync
+unsafe impl Send for PortableParams {}
+unsafe impl Sync for PortableParams {}
+
+impl PortableParams {
+    pub(crate) fn new(params: SpawnParams) -> Option<Self> {
+        match params.data {
+            Some(TaskData::Local(_)) => None,
+            _ => Some(Self(params)),
+        }
+    }
+    pub(crate) fn into_inner(self) -> SpawnParams { self.0 }
+}
+
+/// Creates cleanup future of cancelled task, compared and hashed by identity.
+#[derive(Clone)]
+pub(crate) struct CancelHandler(Arc<dyn Fn() -> BoxedFuture<'static> + Send + Sync>);
+
+impl CancelHandler {
+    pub(crate) fn create(&self) -> BoxedFuture<'static> { (self.0)() }
+}
+impl PartialEq for CancelHandler {
+    fn eq(&self, other: &Self) -> bool { Arc::ptr_eq(&self.0, &other.0) }
+}
+impl Eq for CancelHandler {}
+impl Hash for CancelHandler {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(Arc::as_ptr(&self.0) as *const u8 as usize); // identity hash code
+    }
+}
+
+/// Decides what happens when task spawned by [`spawn_fallible`](struct.WheelHandle.html#method.spawn_fallible)
+/// returns an error.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum ErrorPolicy {
+    /// Store error and continue execution of other tasks. Stored errors can be obtained with
+    /// [`WheelHandle::take_errors`](struct.WheelHandle.html#method.take_errors). Wheel keeps limited
+    /// number of errors (64 by default, see [`WheelHandle::set_error_capacity`](struct.WheelHandle.html#method.set_error_capacity)),
+    /// oldest ones are discarded first. This is default policy.
+    Log,
+    /// Cancel all tasks from the same [`group`](struct.SpawnParams.html#method.group) as failed task
+    /// and store the error like [`Log`](#variant.Log) does. If failed task doesn't belong to any group
+    /// then only the error is stored.
+    CancelGroup,
+    /// Cancel all tasks in wheel and make wheel's `Future` resolve to this error. If wheel was
+    /// already stopped by another error then this error is stored like [`Log`](#variant.Log) does.
+    StopWheel,
+}
+
+impl Default for ErrorPolicy {
+    fn default() -> Self { ErrorPolicy::Log }
 }
 
 /// Represents state of a task.
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 pub enum State {
     /// Task is currently executing or waiting for its turn to execute.
@@ -62,6 +176,9 @@ pub enum State {
     Cancelled,
     /// Given key has no associated task with it or task completed and was removed from scheduler.
     Inactive,
+    /// Task is cancelled and is running its cleanup future set by
+    /// [`SpawnParams::on_cancel`](struct.SpawnParams.html#method.on_cancel).
+    Cancelling,
 }
 
 macro_rules! unwrap_weak {
@@ -73,8 +190,14 @@ macro_rules! unwrap_weak {
     }
 }
 
-impl<'futures> WheelHandle<'futures> {
-    pub(crate) fn new(ptr: Weak<Algorithm<'futures>>) -> Self { Self { ptr } }
+impl<'futures, T, E> WheelHandle<'futures, T, E> {
+    pub(crate) fn new(ptr: Weak<Algorithm<'futures>>) -> Self { Self { ptr, _phantom: PhantomData } }
+    // call given function with id of each task removed from associated wheel, while hook is alive
+    pub(crate) fn on_remove(&self, hook: &Rc<dyn Fn(IdNum)>) -> bool {
+        let this = unwrap_weak!(self,false);
+        this.on_remove(hook);
+        true
+    }
 
 
     /// Checks if this handle is valid. Handles are weak references bound to specific
@@ -111,7 +234,7 @@ impl<'futures> WheelHandle<'futures> {
     /// assert!(h1.is_same(&h2));
     /// assert!(!h1.is_same(&other));
     /// ```
-    pub fn is_same(&self, other: &WheelHandle<'_>) -> bool {
+    pub fn is_same<U, F>(&self, other: &WheelHandle<'_, U, F>) -> bool {
         let ptr1 = self.ptr.as_ptr() as *const ();
         let ptr2 = other.ptr.as_ptr() as *const ();
         ptr1 == ptr2
@@ -152,8 +275,158 @@ impl<'futures> WheelHandle<'futures> {
     pub fn spawn_dyn<P>(&self, params: P, future: Pin<Box<dyn Future<Output=()> + 'futures>>) -> Option<IdNum>
         where P: Into<SpawnParams>{
         let this = unwrap_weak!(self,None);
-        let dynamic = DynamicFuture::new(future, this.clone_registry(), params.into());
-        this.register(dynamic).and_then(|v|Some(IdNum::from_usize(v)))
+        let params = params.into();
+        let dynamic = DynamicFuture::new(future, this.clone_registry(), &params);
+        this.register(dynamic, &params).and_then(|v|Some(IdNum::from_usize(v)))
+    }
+
+    /// Create new restartable task and obtain its id.
+    ///
+    /// # Arguments
+    /// * `params` - Task creation parameters. Using default will spawn runnable task without name.
+    /// * `factory` - Closure that creates future of this task, it's called once when spawning and
+    /// once for each [`restart`](#method.restart).
+    ///
+    /// Works like [`spawn`](#method.spawn) but task can be restarted. Returns identifier of newly
+    /// allocated task or None if this handle is [`invalid`](#method.is_valid).
+    ///
+    /// # Examples
+    /// ```
+    /// use juggle::{*, dy::*};
+    /// use std::cell::Cell;
+    ///
+    /// let starts = Cell::new(0);
+    /// let wheel = Wheel::new();
+    /// let handle = wheel.handle();
+    /// let id = handle.spawn_factory(SpawnParams::default(), || {
+    ///     starts.set(starts.get() + 1);
+    ///     async { loop { yield_once!(); } }
+    /// }).unwrap();
+    /// let h = handle.clone();
+    /// handle.spawn_default(async move {
+    ///     yield_once!();
+    ///     h.restart(id); // previous future of task is dropped and new one is created
+    ///     yield_once!();
+    ///     h.cancel(id);
+    /// }).unwrap();
+    ///
+    /// smol::block_on(wheel).unwrap();
+    /// assert_eq!(starts.get(), 2);
+    /// ```
+    pub fn spawn_factory<P, C, F>(&self, params: P, mut factory: C) -> Option<IdNum>
+        where C: FnMut() -> F + 'futures, F: Future<Output=()> + 'futures, P: Into<SpawnParams> {
+        let this = unwrap_weak!(self,None);
+        let params = params.into();
+        let factory = Box::new(move || Box::pin(factory()) as Pin<Box<dyn Future<Output=()>>>);
+        let dynamic = DynamicFuture::from_factory(factory, this.clone_registry(), &params);
+        this.register(dynamic, &params).map(IdNum::from_usize)
+    }
+
+    /// Create new task that can be moved to other wheel and obtain its id.
+    ///
+    /// Works like [`spawn`](#method.spawn) but future must be `Send` and `'static`, so that task
+    /// can later be taken out of this wheel by [`detach`](#method.detach). Returns identifier of
+    /// newly allocated task or None if this handle is [`invalid`](#method.is_valid).
+    pub fn spawn_detachable<P, F>(&self, params: P, future: F) -> Option<IdNum>
+        where F: Future<Output=()> + Send + 'static, P: Into<SpawnParams> {
+        let this = unwrap_weak!(self,None);
+        let params = params.into();
+        let dynamic = DynamicFuture::portable(Box::pin(future), this.clone_registry(), &params);
+        this.register(dynamic, &params).map(IdNum::from_usize)
+    }
+
+    /// Create new fallible task and obtain its id.
+    ///
+    /// # Arguments
+    /// * `params` - Task creation parameters. Use [`on_error`](struct.SpawnParams.html#method.on_error)
+    /// to choose what happens when task fails.
+    /// * `future` - The future you want to schedule.
+    ///
+    /// Works like [`spawn`](#method.spawn) but given future returns `Result`. When it returns
+    /// `Err`, error is converted to error type of associated [`Wheel`](struct.Wheel.html) and
+    /// handled according to [`ErrorPolicy`](enum.ErrorPolicy.html) of this task. Returns identifier
+    /// of newly allocated task or None if this handle is [`invalid`](#method.is_valid).
+    ///
+    /// # Examples
+    /// ```
+    /// use juggle::{*, dy::*};
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// enum MyError { Suspended, Io }
+    /// impl From<SuspendError> for MyError {
+    ///     fn from(_: SuspendError) -> Self { MyError::Suspended }
+    /// }
+    ///
+    /// let wheel: Wheel<(), MyError> = Wheel::new_typed();
+    /// wheel.handle().spawn_default(async { loop { yield_once!(); } }).unwrap();
+    /// wheel.handle().spawn_fallible(SpawnParams::default().on_error(ErrorPolicy::StopWheel), async {
+    ///     yield_once!();
+    ///     Err(MyError::Io)
+    /// }).unwrap();
+    ///
+    /// assert_eq!(smol::block_on(wheel), Err(MyError::Io)); // other task was cancelled
+    /// ```
+    pub fn spawn_fallible<P, F, Er>(&self, params: P, future: F) -> Option<IdNum>
+        where F: Future<Output=Result<(), Er>> + 'futures, P: Into<SpawnParams>, Er: Into<E>, E: 'static {
+        let params = params.into();
+        let policy = params.error_policy;
+        let slot = unwrap_weak!(self,None).failure_slot();
+        self.spawn_dyn(params, Box::pin(async move {
+            if let Err(error) = future.await {
+                //wheel handles the error right after this task finishes
+                *slot.borrow_mut() = Some((policy, Box::new(error.into())));
+            }
+        }))
+    }
+
+    /// Stop associated [`Wheel`](struct.Wheel.html) and make it resolve to `Ok(value)`.
+    ///
+    /// All other tasks are cancelled. If called inside task, then wheel stops after this task
+    /// yields or finishes. Returns false if wheel is already stopping (e.g exit was already
+    /// called) or handle is [`invalid`](#method.is_valid).
+    ///
+    /// # Examples
+    /// ```
+    /// use juggle::{*, dy::*};
+    ///
+    /// let wheel: Wheel<u32> = Wheel::new_typed();
+    /// let handle = wheel.handle().clone();
+    /// wheel.handle().spawn_default(async { loop { yield_once!(); } }).unwrap();
+    /// wheel.handle().spawn_default(async move {
+    ///     Yield::times(10).await;
+    ///     handle.exit(42);
+    /// }).unwrap();
+    ///
+    /// assert_eq!(wheel.spin_block(), Ok(42));
+    /// ```
+    pub fn exit(&self, value: T) -> bool where T: 'static {
+        let this = unwrap_weak!(self,false);
+        this.exit(Box::new(value))
+    }
+
+    /// Take errors returned by fallible tasks that failed with [`ErrorPolicy::Log`](enum.ErrorPolicy.html#variant.Log)
+    /// or [`ErrorPolicy::CancelGroup`](enum.ErrorPolicy.html#variant.CancelGroup) policy, in order of
+    /// occurrence. Errors are removed from wheel. Returns empty vector if handle is
+    /// [`invalid`](#method.is_valid).
+    pub fn take_errors(&self) -> Vec<(IdNum, E)> where E: 'static {
+        let this = unwrap_weak!(self,Vec::new());
+        this.take_errors().into_iter()
+            .map(|(id, e)| (id, *e.downcast::<E>().ok().expect("Internal Error: unexpected error type.")))
+            .collect()
+    }
+    /// Set maximum number of stored errors (see [`take_errors`](#method.take_errors)), oldest errors
+    /// that don't fit are discarded. Zero disables storing errors. Returns false if handle is
+    /// [`invalid`](#method.is_valid).
+    pub fn set_error_capacity(&self, capacity: usize) -> bool {
+        let this = unwrap_weak!(self,false);
+        this.set_error_capacity(capacity);
+        true
+    }
+    /// Returns number of errors that were discarded because they didn't fit into wheel, since it
+    /// was created. Returns 0 if handle is [`invalid`](#method.is_valid).
+    pub fn dropped_errors(&self) -> usize {
+        let this = unwrap_weak!(self,0);
+        this.dropped_errors()
     }
 
     /// Cancel task with given id.
@@ -184,6 +457,183 @@ impl<'futures> WheelHandle<'futures> {
         let this = unwrap_weak!(self,false);
         this.resume(id.to_usize())
     }
+    /// Resume task with given id and pass value to it.
+    ///
+    /// Works like [`resume`](#method.resume), additionally given value is returned from
+    /// [`suspend_self`](#method.suspend_self) future awaited by resumed task. Returns false if task
+    /// wasn't suspended or handle is [`invalid`](#method.is_valid), then value is dropped.
+    pub fn resume_with<V: 'static>(&self, id: IdNum, value: V) -> bool {
+        let this = unwrap_weak!(self,false);
+        this.resume_with(id.to_usize(), Box::new(value))
+    }
+    /// Suspend current task until it's resumed by another task.
+    ///
+    /// Returned future suspends task that awaits it and resolves after the task is resumed, with
+    /// value passed to [`resume_with`](#method.resume_with) or `None` when task was resumed by
+    /// [`resume`](#method.resume) (or value had different type than `V`). When awaited outside of
+    /// task of associated wheel (or handle is [`invalid`](#method.is_valid)) it resolves
+    /// immediately to `None`.
+    ///
+    /// # Examples
+    /// ```
+    /// use juggle::{*, dy::*};
+    ///
+    /// let wheel = Wheel::new();
+    /// let handle = wheel.handle().clone();
+    /// let h = handle.clone();
+    /// let waiting = handle.spawn_default(async move {
+    ///     let value: Option<u32> = h.suspend_self().await;
+    ///     assert_eq!(value, Some(42));
+    /// }).unwrap();
+    /// let h = handle.clone();
+    /// handle.spawn_default(async move {
+    ///     yield_once!();
+    ///     assert_eq!(h.get_state(waiting), Some(State::Suspended));
+    ///     h.resume_with(waiting, 42u32);
+    /// }).unwrap();
+    ///
+    /// smol::block_on(wheel).unwrap();
+    /// ```
+    pub fn suspend_self<V: 'static>(&self) -> SuspendSelf<'futures, V> {
+        SuspendSelf { ptr: self.ptr.clone(), key: None, _phantom: PhantomData }
+    }
+    /// Restart task with given id.
+    ///
+    /// Only tasks spawned with [`spawn_factory`](#method.spawn_factory) can be restarted. Current
+    /// future of task is dropped and replaced with new one created by factory. If called inside
+    /// task, then restart happens after this task yields or finishes, so task can also restart
+    /// itself. Suspended task is resumed. Restarted task keeps its id and state of scheduling
+    /// policy. Returns true if task will be restarted, and false if it doesn't exist, can't be
+    /// restarted, is cancelled or handle is [`invalid`](#method.is_valid).
+    pub fn restart(&self, id: IdNum) -> bool {
+        let this = unwrap_weak!(self,false);
+        this.restart(id.to_usize())
+    }
+    /// Set cleanup future of task with given id, it's polled when task is cancelled like cleanup
+    /// set by [`SpawnParams::on_cancel`](struct.SpawnParams.html#method.on_cancel), which it
+    /// replaces. Unlike it, given future doesn't have to be `Send` and can borrow data that
+    /// outlives wheel, e.g. capture this handle or `Rc`. Task with such cleanup can't be
+    /// [`detached`](#method.detach). Returns false if task doesn't exist, is already running its
+    /// cleanup or handle is [`invalid`](#method.is_valid).
+    ///
+    /// # Examples
+    /// ```
+    /// use juggle::{*, dy::*};
+    /// use std::rc::Rc;
+    /// use std::cell::Cell;
+    ///
+    /// let wheel = Wheel::new();
+    /// let handle = wheel.handle().clone();
+    /// let offline = Rc::new(Cell::new(false));
+    /// let id = handle.spawn_default(async { loop { yield_once!(); } }).unwrap();
+    /// let (h, o) = (handle.clone(), offline.clone());
+    /// assert!(handle.on_cancel(id, async move {
+    ///     assert_eq!(h.get_state(h.current().unwrap()), Some(State::Cancelling));
+    ///     o.set(true);
+    /// }));
+    /// handle.cancel(id);
+    /// smol::block_on(wheel).unwrap();
+    /// assert!(offline.get());
+    /// ```
+    pub fn on_cancel<C>(&self, id: IdNum, cleanup: C) -> bool where C: Future<Output=()> + 'futures {
+        let this = unwrap_weak!(self,false);
+        this.set_cleanup(id.to_usize(), Box::pin(cleanup))
+    }
+    /// Take suspended task with given id out of associated [`Wheel`](struct.Wheel.html).
+    ///
+    /// Returned [`DetachedTask`](struct.DetachedTask.html) keeps future of the task in its current
+    /// state, with name and spawn parameters. It can be sent to other thread and registered in
+    /// other wheel by [`attach`](#method.attach), without restarting the task. Wakers that task
+    /// handed out before it was detached will wake the wheel it's attached to. Detached task is
+    /// not recorded in [exit history](#method.recent_exits).
+    ///
+    /// Returns `None` when:
+    /// * Given id is not assigned to any task.
+    /// * Task is not suspended.
+    /// * Called inside task of this wheel, tasks can be detached only between polls of wheel
+    ///   (e.g from future passed to [`Wheel::run_until`](struct.Wheel.html#method.run_until)).
+    /// * Task wasn't spawned by [`spawn_detachable`](#method.spawn_detachable).
+    /// * Task has data that is not `Send`, set by [`SpawnParams::data`](struct.SpawnParams.html#method.data)
+    ///   instead of [`SpawnParams::shared_data`](struct.SpawnParams.html#method.shared_data).
+    /// * Handle is [`invalid`](#method.is_valid).
+    ///
+    /// # Examples
+    /// ```
+    /// use juggle::{*, dy::*};
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// let steps = Arc::new(Mutex::new(Vec::new()));
+    /// let first = Wheel::new();
+    /// let s = steps.clone();
+    /// let id = first.handle().spawn_detachable(SpawnParams::named("Worker"), async move {
+    ///     s.lock().unwrap().push(1);
+    ///     yield_once!();
+    ///     s.lock().unwrap().push(2);
+    /// }).unwrap();
+    /// let handle = first.handle().clone();
+    /// smol::block_on(first.run_until(async {
+    ///     yield_once!(); // worker makes first step
+    ///     handle.suspend(id);
+    /// }));
+    ///
+    /// let task = first.handle().detach(id).unwrap();
+    /// assert_eq!(task.name(), Some("Worker"));
+    /// let second = Wheel::new();
+    /// let moved = second.handle().attach(task).unwrap();
+    /// second.handle().resume(moved);
+    /// smol::block_on(second).unwrap();
+    /// assert_eq!(*steps.lock().unwrap(), vec![1, 2]); // task wasn't restarted
+    /// ```
+    pub fn detach(&self, id: IdNum) -> Option<DetachedTask> {
+        let this = unwrap_weak!(self,None);
+        let task = this.detach(id.to_usize())?;
+        drop(this);
+        task.detach().ok()
+    }
+    /// Register task detached from other wheel by [`detach`](#method.detach) and obtain its id.
+    ///
+    /// Task continues from the point where it was suspended, it keeps its name and spawn
+    /// parameters, and it can be detached again. Attached task is suspended, it must be
+    /// [`resumed`](#method.resume) to run. Returns `None` if handle is [`invalid`](#method.is_valid),
+    /// then task is dropped.
+    pub fn attach(&self, task: DetachedTask) -> Option<IdNum> {
+        let this = unwrap_weak!(self,None);
+        let (dynamic, params) = DynamicFuture::attach(task, this.clone_registry());
+        this.register(dynamic, &params).map(IdNum::from_usize)
+    }
+    /// Change deadline of task with given id.
+    ///
+    /// Returns true if deadline was changed, and false when task doesn't exist, handle is
+    /// [`invalid`](#method.is_valid) or scheduling policy of wheel doesn't support deadlines (e.g
+    /// [`EdfPolicy`](struct.EdfPolicy.html) supports them). Changing deadline allows task to miss
+    /// it again.
+    pub fn set_deadline(&self, id: IdNum, deadline: Deadline) -> bool {
+        let this = unwrap_weak!(self,false);
+        this.set_deadline(id.to_usize(), deadline)
+    }
+    /// Change weight of task with given id.
+    ///
+    /// Returns true if weight was changed, and false when task doesn't exist, handle is
+    /// [`invalid`](#method.is_valid) or scheduling policy of wheel doesn't support weights (e.g
+    /// [`FairSharePolicy`](struct.FairSharePolicy.html) supports them).
+    ///
+    /// # Panics
+    /// Panics if weight is zero.
+    pub fn set_weight(&self, id: IdNum, weight: u16) -> bool {
+        assert_ne!(weight, 0, "Task weight is zero.");
+        let this = unwrap_weak!(self,false);
+        this.set_weight(id.to_usize(), weight)
+    }
+    /// Returns number of deadlines missed by task with given id.
+    ///
+    /// Returns `None` when:
+    /// * Given id is not assigned to any task.
+    /// * Scheduling policy of wheel doesn't track deadlines.
+    /// * Handle is [`invalid`](#method.is_valid).
+    pub fn missed_deadlines(&self, id: IdNum) -> Option<usize> {
+        let this = unwrap_weak!(self,None);
+        this.missed_deadlines(id.to_usize())
+    }
     /// Get state of task with given id.
     ///
     /// If this handle is [`invalid`] then returns
@@ -233,6 +683,124 @@ impl<'futures> WheelHandle<'futures> {
         this.get_current().map(|t| IdNum::from_usize(t))
     }
 
+    /// Yield current task and hand off execution directly to task with given id.
+    ///
+    /// When awaited, current task yields once (like [`Yield::once`](../struct.Yield.html#method.once))
+    /// and given task is woken and polled right after it, regardless of scheduling policy. Task
+    /// polled this way is not polled again by policy in the same beat of wheel. This allows data to
+    /// flow between tasks without waiting for whole beat of wheel. Resolves to false if given task can't be polled (e.g it doesn't exist, is suspended,
+    /// cancelled, is the current task or handle is [`invalid`](#method.is_valid)), then it only
+    /// yields current task.
+    ///
+    /// # Examples
+    /// ```
+    /// use juggle::{*, dy::*};
+    /// use std::cell::RefCell;
+    ///
+    /// let log = RefCell::new(Vec::new());
+    /// let wheel = Wheel::with_policy(FifoPolicy::new());
+    /// let handle = wheel.handle();
+    /// let consumer = handle.spawn_default(async {
+    ///     loop {
+    ///         log.borrow_mut().push("consume");
+    ///         yield_once!();
+    ///     }
+    /// }).unwrap();
+    /// let (h, log) = (handle.clone(), &log);
+    /// handle.spawn_default(async move {
+    ///     for _ in 0..2 {
+    ///         log.borrow_mut().push("produce");
+    ///         h.switch_to(consumer).await;
+    ///     }
+    ///     h.cancel(consumer);
+    /// }).unwrap();
+    ///
+    /// smol::block_on(wheel).unwrap();
+    /// let log = log.borrow();
+    /// // consumer is polled right after each produced item
+    /// assert!(log.windows(2).filter(|w| w[0] == "produce").all(|w| w[1] == "consume"));
+    /// ```
+    pub fn switch_to(&self, id: IdNum) -> SwitchTo<'futures> {
+        SwitchTo { ptr: self.ptr.clone(), id, accepted: None }
+    }
+
+    /// Wait until all other tasks are idle.
+    ///
+    /// Returned future resolves at the end of beat in which no task was polled, so all other tasks
+    /// are waiting for some event, suspended or finished. It's useful when current task wants to
+    /// observe consistent state of the wheel (e.g take [`snapshot`](#method.snapshot)). Resolves
+    /// immediately if handle is [`invalid`](#method.is_valid).
+    ///
+    /// # Examples
+    /// ```
+    /// use juggle::{*, dy::*};
+    /// use std::cell::Cell;
+    ///
+    /// let counter = Cell::new(0);
+    /// let wheel = Wheel::new();
+    /// let handle = wheel.handle().clone();
+    /// for _ in 0..3 {
+    ///     handle.spawn_default(async {
+    ///         for _ in 0..10 {
+    ///             counter.set(counter.get() + 1);
+    ///             yield_once!();
+    ///         }
+    ///     }).unwrap();
+    /// }
+    /// let (h, counter) = (handle.clone(), &counter);
+    /// handle.spawn_default(async move {
+    ///     h.wait_idle().await;
+    ///     assert_eq!(counter.get(), 30); // other tasks finished
+    /// }).unwrap();
+    ///
+    /// smol::block_on(wheel).unwrap();
+    /// ```
+    pub fn wait_idle(&self) -> WaitIdle<'futures> {
+        WaitIdle { ptr: self.ptr.clone(), slot: None }
+    }
+
+    /// Create scope in which tasks can borrow data that lives shorter than this wheel.
+    ///
+    /// Given closure receives handle to nested wheel owned by returned future, tasks spawned by
+    /// this handle can borrow data from enclosing task (e.g its local variables). Returned future
+    /// polls closure's future together with one beat of nested tasks each time it's polled (so
+    /// other tasks of this wheel are not blocked), and resolves when closure's future and
+    /// all nested tasks have finished or were cancelled. It resolves to value returned from
+    /// closure's future, or [`SuspendError`](struct.SuspendError.html) when all remaining nested
+    /// tasks became suspended. Dropping the future cancels all nested tasks.
+    ///
+    /// # Examples
+    /// ```
+    /// use juggle::{*, dy::*};
+    ///
+    /// let wheel = Wheel::new();
+    /// let handle = wheel.handle().clone();
+    /// let h = handle.clone();
+    /// handle.spawn_default(async move {
+    ///     let mut parts = vec![0; 4]; // local data of this task
+    ///     let borrowed = &mut parts;
+    ///     let result = h.scope(|s| async move {
+    ///         for (i, part) in borrowed.iter_mut().enumerate() {
+    ///             s.spawn_default(async move {
+    ///                 yield_once!();
+    ///                 *part = i * 10; // borrows data from enclosing task
+    ///             }).unwrap();
+    ///         }
+    ///         "spawned"
+    ///     }).await;
+    ///     assert_eq!(result, Ok("spawned"));
+    ///     assert_eq!(parts, [0, 10, 20, 30]);
+    /// }).unwrap();
+    ///
+    /// smol::block_on(wheel).unwrap();
+    /// ```
+    pub fn scope<'s, F, Fut, R>(&self, body: F) -> Scope<'s, R>
+        where F: FnOnce(WheelHandle<'s>) -> Fut, Fut: Future<Output=R> + 's {
+        let wheel = Wheel::new();
+        let future = body(wheel.handle().clone());
+        Scope { wheel, body: Some(Box::pin(future)), result: None }
+    }
+
     /// Applies given function on reference to given task name and returns result of that function.
     ///
     /// The argument that is passed to `func` is `None` when:
@@ -254,7 +822,7 @@ impl<'futures> WheelHandle<'futures> {
     /// assert!(wheel.handle().with_name(none, |name| name == None));
     /// ```
     ///
-    pub fn with_name<F, T>(&self, id: IdNum, func: F) -> T where F: FnOnce(Option<&str>) -> T {
+    pub fn with_name<F, R>(&self, id: IdNum, func: F) -> R where F: FnOnce(Option<&str>) -> R {
         let this = unwrap_weak!(self,func(None));
         this.with_name(id.to_usize(),move |name|func(name.as_str()))
     }
@@ -284,13 +852,7 @@ impl<'futures> WheelHandle<'futures> {
     /// for string use in such case, use [`with_name`](#method.with_name).
     pub fn get_name(&self, id: IdNum) -> Option<Cow<'static,str>> {
         let this = unwrap_weak!(self,None);
-        this.with_name(id.to_usize(),move |name|{
-            match name {
-                TaskName::Static(s) => Some(Cow::<'static,str>::Borrowed(s)),
-                TaskName::Dynamic(s) => Some(Cow::<'static,str>::Owned(s.to_string())),
-                TaskName::None => None,
-            }
-        })
+        this.with_name(id.to_usize(),move |name|name.to_cow())
     }
     /// Find task id that has name equal to given argument.
     ///
@@ -301,6 +863,82 @@ impl<'futures> WheelHandle<'futures> {
         let this = unwrap_weak!(self,None);
         this.get_by_name(name).map(|k| IdNum::from_usize(k))
     }
+    /// Returns value of type `C` provided to associated wheel by
+    /// [`Wheel::provide`](struct.Wheel.html#method.provide). Returns `None` if there is no such value
+    /// or handle is [`invalid`](#method.is_valid).
+    ///
+    /// Value is shared instead of borrowed because it can be replaced by next call to `provide`
+    /// and handle can outlive its wheel. To avoid cloning `Rc`, use
+    /// [`with_context`](#method.with_context).
+    pub fn context<C: 'static>(&self) -> Option<Rc<C>> {
+        let this = unwrap_weak!(self,None);
+        this.context()
+    }
+    /// Call given function with value of type `C` provided to associated wheel by
+    /// [`Wheel::provide`](struct.Wheel.html#method.provide) and return its result. Works like
+    /// [`context`](#method.context), function is not called when there is no such value.
+    pub fn with_context<C: 'static, F, R>(&self, func: F) -> Option<R> where F: FnOnce(&C) -> R {
+        self.context().map(|value| func(&value))
+    }
+    /// Change name of task with given id to static string slice.
+    ///
+    /// Returns false if there is no task with given id, handle is [`invalid`](#method.is_valid)
+    /// or name of this task is currently read by [`with_name`](#method.with_name) (e.g when called
+    /// from its closure).
+    ///
+    /// # Examples
+    /// ```
+    /// use juggle::{*, dy::*};
+    ///
+    /// let wheel = Wheel::new();
+    /// let handle = wheel.handle().clone();
+    /// wheel.handle().spawn(SpawnParams::named("Starting"), async move {
+    ///     let id = handle.current().unwrap();
+    ///     // ...
+    ///     assert!(handle.set_name(id, "Serving"));
+    ///     assert_eq!(handle.get_by_name("Serving"), Some(id));
+    /// }).unwrap();
+    /// smol::block_on(wheel).unwrap();
+    /// ```
+    pub fn set_name(&self, id: IdNum, name: &'static str) -> bool {
+        let this = unwrap_weak!(self,false);
+        this.set_name(id.to_usize(), TaskName::Static(name))
+    }
+    /// Change name of task with given id to dynamically allocated string. Works like
+    /// [`set_name`](#method.set_name).
+    pub fn set_dyn_name(&self, id: IdNum, name: impl Into<String>) -> bool {
+        let this = unwrap_weak!(self,false);
+        this.set_name(id.to_usize(), TaskName::Dynamic(name.into().into_boxed_str()))
+    }
+    /// Call given function with data attached to task with given id by
+    /// [`SpawnParams::data`](struct.SpawnParams.html#method.data) and return its result.
+    ///
+    /// Returns `None` (without calling function) when:
+    /// * Task has no data or data is not of type `D`.
+    /// * Given id is not assigned to any task.
+    /// * Handle is [`invalid`](#method.is_valid).
+    ///
+    /// Function can use this handle freely, wheel is not borrowed while it executes.
+    ///
+    /// # Examples
+    /// ```
+    /// use juggle::{*, dy::*};
+    ///
+    /// struct Owner { module: &'static str }
+    ///
+    /// let wheel = Wheel::new();
+    /// let params = SpawnParams::named("Reader").data(Owner { module: "sensors" });
+    /// let id = wheel.handle().spawn(params, async {/*...*/}).unwrap();
+    ///
+    /// assert_eq!(wheel.handle().with_data(id, |o: &Owner| o.module), Some("sensors"));
+    /// assert_eq!(wheel.handle().with_data(id, |v: &u32| *v), None); // different type
+    /// ```
+    pub fn with_data<D: Any, F, R>(&self, id: IdNum, func: F) -> Option<R> where F: FnOnce(&D) -> R {
+        let this = unwrap_weak!(self,None);
+        let data = this.get_data(id.to_usize())?;
+        drop(this);
+        data.downcast_ref().map(func)
+    }
 
     /// Returns number of total registered tasks in this scheduler at the moment or 0 if this handle
     /// is [`invalid`](#method.is_valid).
@@ -309,24 +947,120 @@ impl<'futures> WheelHandle<'futures> {
         this.registered_count()
     }
 
+    /// Take snapshot of all tasks registered in associated [`Wheel`](struct.Wheel.html).
+    ///
+    /// Returned [`WheelSnapshot`](struct.WheelSnapshot.html) is a plain copy of wheel content, it
+    /// lists every task with its identifier, name and state. Returns `None` if this handle is
+    /// [`invalid`](#method.is_valid).
+    ///
+    /// # Examples
+    /// ```
+    /// use juggle::{*, dy::*};
+    ///
+    /// let wheel = Wheel::new();
+    /// let handle = wheel.handle().clone();
+    /// let id = wheel.handle().spawn(SpawnParams::named("Main"), async move {
+    ///     let snapshot = handle.snapshot().unwrap();
+    ///     assert_eq!(snapshot.current, handle.current());
+    ///     assert!(snapshot.get(handle.current().unwrap()).unwrap().current);
+    /// }).unwrap();
+    ///
+    /// assert_eq!(wheel.handle().snapshot().unwrap().get(id).unwrap().state, State::Runnable);
+    /// smol::block_on(wheel).unwrap();
+    /// ```
+    pub fn snapshot(&self) -> Option<WheelSnapshot> {
+        let this = unwrap_weak!(self,None);
+        Some(this.snapshot())
+    }
+
+    /// Returns iterator over information about all tasks registered in associated
+    /// [`Wheel`](struct.Wheel.html).
+    ///
+    /// Equivalent to iterating over tasks of [`snapshot`](#method.snapshot). Iterator is empty
+    /// if this handle is [`invalid`](#method.is_valid).
+    pub fn tasks(&self) -> impl Iterator<Item=TaskInfo> {
+        self.snapshot().unwrap_or_default().into_iter()
+    }
+
+    /// Returns most recent exit record of task with given id, or `None` if there is no such record
+    /// in exit history or handle is [`invalid`](#method.is_valid).
+    ///
+    /// Wheel keeps records of last 32 tasks that ended (completed, were cancelled, failed...),
+    /// this can be changed with [`set_history_capacity`](#method.set_history_capacity). Since ids
+    /// are reused, returned record might belong to previous task with the same id.
+    ///
+    /// # Examples
+    /// ```
+    /// use juggle::{*, dy::*};
+    ///
+    /// let wheel = Wheel::new();
+    /// let handle = wheel.handle().clone();
+    /// let id1 = handle.spawn(SpawnParams::named("Short"), async {}).unwrap();
+    /// let id2 = handle.spawn_default(async { loop { yield_once!(); } }).unwrap();
+    /// let h = handle.clone();
+    /// handle.spawn_default(async move {
+    ///     h.cancel(id2);
+    ///     Yield::times(2).await;
+    ///     let status = h.exit_status(id1).unwrap();
+    ///     assert_eq!((status.name.as_deref(), status.reason), (Some("Short"), ExitReason::Completed));
+    ///     assert_eq!(h.exit_status(id2).unwrap().reason, ExitReason::Cancelled);
+    /// }).unwrap();
+    /// smol::block_on(wheel).unwrap();
+    /// ```
+    pub fn exit_status(&self, id: IdNum) -> Option<ExitStatus> {
+        let this = unwrap_weak!(self,None);
+        this.exit_status(id)
+    }
+
+    /// Returns exit records of tasks that recently ended, from oldest to newest. Returns empty
+    /// vector if handle is [`invalid`](#method.is_valid). See [`exit_status`](#method.exit_status).
+    pub fn recent_exits(&self) -> Vec<ExitStatus> {
+        let this = unwrap_weak!(self,Vec::new());
+        this.recent_exits()
+    }
+
+    /// Set maximum number of records in exit history, oldest records that don't fit are discarded.
+    /// Zero disables exit history. Returns false if handle is [`invalid`](#method.is_valid).
+    pub fn set_history_capacity(&self, capacity: usize) -> bool {
+        let this = unwrap_weak!(self,false);
+        this.set_history_capacity(capacity);
+        true
+    }
+
+    /// Set clock used to record time when task ended in
+    /// [`ExitStatus::exit_time`](struct.ExitStatus.html#structfield.exit_time). Time is measured
+    /// from the moment of calling this method, records made before have no time. Returns false if
+    /// handle is [`invalid`](#method.is_valid).
+    pub fn set_history_clock<C>(&self, clock: C) -> bool
+        where C: TimerClock<Duration=Duration> + 'static, C::Instant: Clone + 'static {
+        let this = unwrap_weak!(self,false);
+        let start = clock.start();
+        this.set_history_clock(Box::new(move || clock.stop(start.clone())));
+        true
+    }
+
     fn fmt_name(&self, f: &mut Formatter<'_>, name: &str) -> core::fmt::Result {
         let this = unwrap_weak!(self,write!(f,"{}{{ Invalid }}",name));
         this.format_internal(f, name)
     }
 }
 
-impl<'futures> Debug for WheelHandle<'futures> {
+impl<'futures, T, E> Clone for WheelHandle<'futures, T, E> {
+    fn clone(&self) -> Self { Self::new(self.ptr.clone()) }
+}
+
+impl<'futures, T, E> Debug for WheelHandle<'futures, T, E> {
     fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         self.fmt_name(f, "WheelHandle")
     }
 }
 
-impl<'futures> PartialEq for WheelHandle<'futures> {
+impl<'futures, T, E> PartialEq for WheelHandle<'futures, T, E> {
     fn eq(&self, other: &Self) -> bool { self.is_same(other) }
 }
-impl<'futures> Eq for WheelHandle<'futures> {}
+impl<'futures, T, E> Eq for WheelHandle<'futures, T, E> {}
 
-impl<'futures> Hash for WheelHandle<'futures> {
+impl<'futures, T, E> Hash for WheelHandle<'futures, T, E> {
     fn hash<H: Hasher>(&self, state: &mut H) {
         state.write_usize(self.ptr.as_ptr() as usize); // identity hash code
     }
@@ -348,6 +1082,121 @@ impl SpawnParams {
         self.name = TaskName::Dynamic(name.into().into_boxed_str());
         self
     }
+    /// Set deadline property, used by deadline aware policies such as
+    /// [`EdfPolicy`](struct.EdfPolicy.html).
+    pub fn deadline(mut self, deadline: Deadline) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+    /// Set weight property, used by weight aware policies such as
+    /// [`FairSharePolicy`](struct.FairSharePolicy.html). Default weight is 1.
+    ///
+    /// # Panics
+    /// Panics if weight is zero.
+    pub fn weight(mut self, weight: u16) -> Self {
+        self.weight = NonZeroU16::new(weight).expect("Task weight is zero.");
+        self
+    }
+    /// Set error policy property, used by tasks spawned with
+    /// [`spawn_fallible`](struct.WheelHandle.html#method.spawn_fallible). Default is `ErrorPolicy::Log`.
+    pub fn on_error(mut self, policy: ErrorPolicy) -> Self {
+        self.error_policy = policy;
+        self
+    }
+    /// Set group property. Tasks with the same group are cancelled together when one of them fails
+    /// with [`ErrorPolicy::CancelGroup`](enum.ErrorPolicy.html#variant.CancelGroup) policy.
+    pub fn group(mut self, group: u32) -> Self {
+        self.group = Some(group);
+        self
+    }
+    /// Set cleanup property. When task is cancelled, its future is dropped and then future created
+    /// by given function is polled under the same id, with task in `Cancelling` state. Cleanup
+    /// future can await async operations that `Drop` can't, but its polls are limited: when it's not
+    /// finished after [`epilogue_poll_limit`](#method.epilogue_poll_limit) polls then it's dropped.
+    ///
+    /// Cleanup is not run when whole wheel is stopped, or when task finishes on its own. Cancelling
+    /// task that is already running its cleanup has no effect.
+    ///
+    /// Parameters can be sent to other threads (e.g. with [`WheelSet`](struct.WheelSet.html)) and
+    /// are kept by [`DetachedTask`](struct.DetachedTask.html), so function must be `Send` and
+    /// `Sync`. To set cleanup that captures handle or other local data use
+    /// [`WheelHandle::on_cancel`](struct.WheelHandle.html#method.on_cancel).
+    ///
+    /// # Examples
+    /// ```
+    /// # extern crate alloc;
+    /// # use juggle::*;
+    /// # use juggle::dy::*;
+    /// # use alloc::sync::Arc;
+    /// # use core::sync::atomic::{AtomicBool, Ordering};
+    /// let wheel = Wheel::new();
+    /// let handle = wheel.handle().clone();
+    /// let offline = Arc::new(AtomicBool::new(false));
+    /// let o = offline.clone();
+    /// let params = SpawnParams::default().on_cancel(move || {
+    ///     let o = o.clone();
+    ///     async move {
+    ///         yield_once!(); // e.g. send "going offline" message
+    ///         o.store(true, Ordering::Relaxed);
+    ///     }
+    /// });
+    /// let id = handle.spawn(params, async { loop { yield_once!(); } }).unwrap();
+    /// let h = handle.clone();
+    /// handle.spawn_default(async move {
+    ///     h.cancel(id);
+    ///     yield_once!();
+    ///     assert_eq!(h.get_state(id), Some(State::Cancelling));
+    /// }).unwrap();
+    /// smol::block_on(wheel).unwrap();
+    /// assert!(offline.load(Ordering::Relaxed));
+    /// ```
+    pub fn on_cancel<F, C>(mut self, cleanup: F) -> Self
+        where F: Fn() -> C + Send + Sync + 'static, C: Future<Output=()> + 'static {
+        self.on_cancel = Some(CancelHandler(Arc::new(move || Box::pin(cleanup()) as BoxedFuture<'static>)));
+        self
+    }
+    /// Set maximum number of polls of cleanup future set by [`on_cancel`](#method.on_cancel).
+    /// Default limit is 16 polls.
+    ///
+    /// Limit counts polls instead of time, so it doesn't depend on clock of wheel (e.g clock given
+    /// to [`Wheel::poll_for`](struct.Wheel.html#method.poll_for)) and it bounds cleanup that keeps
+    /// waking itself. Cleanup that waits for external events should apply its own timeout.
+    ///
+    /// # Panics
+    /// Panics if limit is zero.
+    pub fn epilogue_poll_limit(mut self, polls: usize) -> Self {
+        assert_ne!(polls, 0, "Epilogue poll limit is zero.");
+        self.epilogue_poll_limit = polls;
+        self
+    }
+    /// Set data property. Data is attached to spawned task and can be read by any task with
+    /// [`WheelHandle::with_data`](struct.WheelHandle.html#method.with_data). Cloned parameters
+    /// share the same data, so all tasks spawned with them see the same value.
+    ///
+    /// Data doesn't have to be `Send`, so task that has it can't be
+    /// [`detached`](struct.WheelHandle.html#method.detach) and parameters can't be used with
+    /// [`WheelSetHandle::spawn_on_with`](struct.WheelSetHandle.html#method.spawn_on_with). Use
+    /// [`shared_data`](#method.shared_data) for such tasks.
+    pub fn data<T: Any>(mut self, value: T) -> Self {
+        self.data = Some(TaskData::Local(Rc::new(value)));
+        self
+    }
+    /// Set data property to value that can be sent to other threads. Works like
+    /// [`data`](#method.data), but task that has such data can be detached and moved to other
+    /// wheel.
+    pub fn shared_data<T: Any + Send + Sync>(mut self, value: T) -> Self {
+        self.data = Some(TaskData::Shared(Arc::new(value)));
+        self
+    }
+    /// Add tag to tags property. Tags are not used by scheduler, they are reported in
+    /// [`TaskInfo`](struct.TaskInfo.html) so that tasks can be grouped when inspecting wheel.
+    /// Adding tag that is already present does nothing.
+    pub fn tag(mut self, tag: &'static str) -> Self {
+        if !self.tags.contains(&tag) {
+            self.tags.push(tag);
+        }
+        self
+    }
     /// Create default parameters (suspended property set to false) with name property set to
     /// static string slice.
     pub fn named(name: &'static str) -> Self { Self::default().name(name) }
@@ -357,6 +1206,26 @@ impl SpawnParams {
     /// Create default parameters (name property set to none) with suspended property set to given
     /// value.
     pub fn suspended(value: bool) -> Self { Self::default().suspend(value) }
+    /// Create default parameters with deadline property set to given value.
+    pub fn with_deadline(deadline: Deadline) -> Self { Self::default().deadline(deadline) }
+    /// Returns value of suspended property.
+    pub fn is_suspended(&self) -> bool { self.suspended }
+    /// Returns value of name property or `None` if task will be unnamed.
+    pub fn get_name(&self) -> Option<&str> { self.name.as_str() }
+    /// Returns value of deadline property or `None` if task has no deadline.
+    pub fn get_deadline(&self) -> Option<Deadline> { self.deadline }
+    /// Returns value of weight property.
+    pub fn get_weight(&self) -> u16 { self.weight.get() }
+    /// Returns value of error policy property.
+    pub fn get_error_policy(&self) -> ErrorPolicy { self.error_policy }
+    /// Returns value of group property or `None` if task doesn't belong to any group.
+    pub fn get_group(&self) -> Option<u32> { self.group }
+    /// Returns true if cleanup future was set with [`on_cancel`](#method.on_cancel).
+    pub fn has_on_cancel(&self) -> bool { self.on_cancel.is_some() }
+    /// Returns value of epilogue poll limit property.
+    pub fn get_epilogue_poll_limit(&self) -> usize { self.epilogue_poll_limit }
+    /// Returns value of data property or `None` if there is no data or it has different type.
+    pub fn get_data<T: Any>(&self) -> Option<&T> { self.data.as_ref()?.downcast_ref() }
 }
 
 impl Default for SpawnParams {
@@ -365,6 +1234,14 @@ impl Default for SpawnParams {
         Self {
             suspended: false,
             name: TaskName::None,
+            deadline: None,
+            weight: NonZeroU16::new(1).unwrap(),
+            error_policy: ErrorPolicy::Log,
+            group: None,
+            on_cancel: None,
+            epilogue_poll_limit: 16,
+            data: None,
+            tags: Vec::new(),
         }
     }
 }
@@ -392,4 +1269,140 @@ impl From<String> for SpawnParams {
 impl From<bool> for SpawnParams {
     /// Works as [`suspended`](struct.SpawnParams.html#method.suspended) method.
     fn from(v: bool) -> Self { Self::suspended(v) }
-}
\ No newline at end of file
+}
+
+/// Future returned by [`WheelHandle::switch_to`](struct.WheelHandle.html#method.switch_to).
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct SwitchTo<'futures> {
+    ptr: Weak<Algorithm<'futures>>,
+    id: IdNum,
+    accepted: Option<bool>,
+}
+
+impl Future for SwitchTo<'_> {
+    type Output = bool;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        match this.accepted {
+            Some(accepted) => Poll::Ready(accepted),
+            None => {
+                let accepted = this.ptr.upgrade().map_or(false, |alg| alg.switch_to(this.id.to_usize()));
+                this.accepted = Some(accepted);
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl Debug for SwitchTo<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SwitchTo").field("id", &self.id).field("accepted", &self.accepted).finish()
+    }
+}
+
+/// Future returned by [`WheelHandle::suspend_self`](struct.WheelHandle.html#method.suspend_self).
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct SuspendSelf<'futures, V> {
+    ptr: Weak<Algorithm<'futures>>,
+    key: Option<usize>, //id of suspended task
+    _phantom: PhantomData<fn() -> V>,
+}
+
+impl<V: 'static> Future for SuspendSelf<'_, V> {
+    type Output = Option<V>;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let alg = match this.ptr.upgrade() {
+            Some(alg) => alg,
+            None => return Poll::Ready(None),
+        };
+        match this.key {
+            //suspended tasks are not polled, so task was resumed
+            Some(key) => Poll::Ready(alg.take_resume_value(key).and_then(|v| v.downcast().ok()).map(|v| *v)),
+            None => {
+                let key = match alg.get_current() {
+                    Some(key) => key,
+                    None => return Poll::Ready(None),
+                };
+                drop(alg.take_resume_value(key)); //forget values passed to this task before
+                if !alg.suspend(key) {
+                    return Poll::Ready(None);
+                }
+                this.key = Some(key);
+                cx.waker().wake_by_ref(); //runnable as soon as it's resumed
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<V> Debug for SuspendSelf<'_, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SuspendSelf").field("suspended", &self.key.is_some()).finish()
+    }
+}
+
+/// Future returned by [`WheelHandle::wait_idle`](struct.WheelHandle.html#method.wait_idle).
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct WaitIdle<'futures> {
+    ptr: Weak<Algorithm<'futures>>,
+    slot: Option<IdleSlot>,
+}
+
+impl Future for WaitIdle<'_> {
+    type Output = ();
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        match this.ptr.upgrade() {
+            Some(alg) => alg.poll_idle(&mut this.slot, cx),
+            None => Poll::Ready(()),
+        }
+    }
+}
+
+impl Debug for WaitIdle<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("WaitIdle").finish()
+    }
+}
+
+/// Future returned by [`WheelHandle::scope`](struct.WheelHandle.html#method.scope).
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Scope<'s, R> {
+    wheel: Wheel<'s>,
+    body: Option<Pin<Box<dyn Future<Output=R> + 's>>>,
+    result: Option<R>,
+}
+
+impl<R> Unpin for Scope<'_, R> {} //result is never pinned
+
+impl<R> Future for Scope<'_, R> {
+    type Output = Result<R, SuspendError>;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        if let Some(body) = this.body.as_mut() {
+            if let Poll::Ready(result) = body.as_mut().poll(cx) {
+                this.result = Some(result);
+                this.body = None;
+            }
+        }
+        let outcome = match this.wheel.poll_beat(cx) {
+            Poll::Ready(outcome) => outcome,
+            Poll::Pending => return Poll::Pending,
+        };
+        match this.result.take() {
+            Some(result) => Poll::Ready(match outcome {
+                Outcome::Suspended => Err(SuspendError),
+                _ => Ok(result),
+            }),
+            None => Poll::Pending, //closure's future can still spawn or resume tasks
+        }
+    }
+}
+
+impl<R> Debug for Scope<'_, R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Scope").field("wheel", &self.wheel).field("finished", &self.body.is_none()).finish()
+    }
+}
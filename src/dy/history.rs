@@ -0,0 +1,85 @@
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::time::Duration;
+use crate::dy::IdNum;
+
+/// Reason why task was removed from [`Wheel`](struct.Wheel.html).
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(u8)]
+pub enum ExitReason {
+    /// Task future completed.
+    Completed,
+    /// Task was cancelled, or it was still registered when wheel was stopped.
+    Cancelled,
+    /// Task spawned by [`spawn_fallible`](struct.WheelHandle.html#method.spawn_fallible) returned
+    /// an error.
+    Failed,
+    /// Task panicked while it was polled. Task stays registered in wheel, but it's recorded
+    /// so that the reason can be found after unwinding.
+    Panicked,
+    /// Cleanup future of cancelled task (see [`SpawnParams::on_cancel`](struct.SpawnParams.html#method.on_cancel))
+    /// didn't finish within its poll limit.
+    TimedOut,
+}
+
+/// Record of task that ended, kept in exit history of [`Wheel`](struct.Wheel.html).
+///
+/// See [`WheelHandle::exit_status`](struct.WheelHandle.html#method.exit_status) and
+/// [`WheelHandle::recent_exits`](struct.WheelHandle.html#method.recent_exits).
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExitStatus {
+    /// Identifier of task, note that it might be already reused by another task.
+    pub id: IdNum,
+    /// Name of task or `None` if task was unnamed.
+    pub name: Option<Cow<'static, str>>,
+    /// Why task ended.
+    pub reason: ExitReason,
+    /// Number of wheel beat in which task ended, beats are counted from 1 since wheel was created.
+    /// Beats can take any amount of time, so this value only orders records: task with larger
+    /// value ended later, tasks with equal values ended in the same beat.
+    pub exit_beat: u64,
+    /// Time when task ended, measured from the moment when clock was given to wheel by
+    /// [`WheelHandle::set_history_clock`](struct.WheelHandle.html#method.set_history_clock).
+    /// `None` if wheel had no clock at that time.
+    pub exit_time: Option<Duration>,
+}
+
+// bounded ring of recently ended tasks, oldest are discarded first
+pub(crate) struct ExitHistory {
+    records: VecDeque<ExitStatus>,
+    capacity: usize,
+    clock: Option<Box<dyn Fn() -> Duration>>, //returns time elapsed since clock was set
+}
+
+impl ExitHistory {
+    pub(crate) const DEFAULT_CAPACITY: usize = 32;
+
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self { records: VecDeque::new(), capacity, clock: None }
+    }
+    pub(crate) fn is_enabled(&self) -> bool { self.capacity != 0 }
+    pub(crate) fn set_clock(&mut self, clock: Box<dyn Fn() -> Duration>) { self.clock = Some(clock) }
+    pub(crate) fn elapsed(&self) -> Option<Duration> { self.clock.as_ref().map(|clock| clock()) }
+    pub(crate) fn push(&mut self, status: ExitStatus) {
+        if self.capacity == 0 { return; }
+        if self.records.len() == self.capacity {
+            self.records.pop_front();
+        }
+        self.records.push_back(status);
+    }
+    pub(crate) fn set_capacity(&mut self, capacity: usize) {
+        while self.records.len() > capacity {
+            self.records.pop_front();
+        }
+        self.capacity = capacity;
+    }
+    // most recent record of task with given id
+    pub(crate) fn get(&self, id: IdNum) -> Option<&ExitStatus> {
+        self.records.iter().rev().find(|s| s.id == id)
+    }
+    pub(crate) fn to_vec(&self) -> Vec<ExitStatus> { self.records.iter().cloned().collect() }
+}
@@ -0,0 +1,54 @@
+use alloc::vec::Vec;
+use core::cell::Cell;
+use core::mem;
+use core::task::{Context, Poll, Waker};
+use crate::utils::Ucw;
+
+// tasks waiting for a beat of wheel in which no task was polled, used by dynamic and static wheels
+pub(crate) struct IdleWaiters {
+    generation: Cell<usize>, //number of idle beats that had waiters
+    waiters: Ucw<Vec<Waker>>,
+}
+
+// stored by waiting future between polls
+#[derive(Copy, Clone)]
+pub(crate) struct IdleSlot {
+    generation: usize, //generation of first poll
+    index: usize, //position of waker in waiters of that generation
+}
+
+impl IdleWaiters {
+    pub const fn new() -> Self {
+        Self { generation: Cell::new(0), waiters: Ucw::new(Vec::new()) }
+    }
+    // ready when at least one idle beat passed after first poll, waker registered in 'slot' is
+    // replaced on next polls so that repeatedly polled future holds only one waker
+    pub fn poll_wait(&self, slot: &mut Option<IdleSlot>, cx: &mut Context<'_>) -> Poll<()> {
+        let generation = self.generation.get();
+        let mut waiters = self.waiters.borrow_mut();
+        match *slot {
+            Some(s) if s.generation != generation => return Poll::Ready(()),
+            Some(s) => {
+                let waker = &mut waiters[s.index];
+                if !waker.will_wake(cx.waker()) {
+                    *waker = cx.waker().clone();
+                }
+            }
+            None => {
+                *slot = Some(IdleSlot { generation, index: waiters.len() });
+                waiters.push(cx.waker().clone());
+            }
+        }
+        Poll::Pending
+    }
+    // call at the end of beat in which no task was polled, returns true if any waiter was woken
+    pub fn notify_idle(&self) -> bool {
+        let waiters = mem::replace(&mut *self.waiters.borrow_mut(), Vec::new());
+        if waiters.is_empty() {
+            return false;
+        }
+        self.generation.set(self.generation.get().wrapping_add(1));
+        waiters.into_iter().for_each(Waker::wake); //wakers don't run user code
+        true
+    }
+}
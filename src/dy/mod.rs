@@ -4,13 +4,34 @@ mod wheel;
 mod handle;
 mod registry;
 pub(crate) mod stat;
-mod unordered_algorithm;
+pub(crate) mod idle;
+mod policy;
+mod deadline;
+mod fair_share;
+mod snapshot;
+mod history;
+mod supervisor;
+#[cfg(feature = "std")]
+mod thread;
+#[cfg(feature = "std")]
+mod set;
 
-pub use self::handle::{IdNum, SpawnParams, State, WheelHandle};
-pub use self::wheel::{LockedWheel, SuspendError, Wheel};
+pub use self::handle::{ErrorPolicy, IdNum, Scope, SpawnParams, State, SuspendSelf, SwitchTo, WaitIdle, WheelHandle};
+pub use self::wheel::{LockedWheel, RunUntil, SuspendError, Wheel};
+pub use self::dyn_future::DetachedTask;
+pub use self::snapshot::{TaskInfo, WheelSnapshot};
+pub use self::history::{ExitReason, ExitStatus};
+pub use self::policy::{FifoPolicy, PolicyContext, SchedulingPolicy, UnorderedPolicy};
+pub use self::deadline::{Deadline, EdfPolicy};
+pub use self::fair_share::FairSharePolicy;
+pub use self::supervisor::{RestartLimitError, RestartStrategy, Supervisor};
+#[cfg(feature = "std")]
+pub use self::thread::{Completion, WheelThread};
+#[cfg(feature = "std")]
+pub use self::set::{Receive, TaskAddr, WheelSet, WheelSetHandle};
 
-use crate::dy::unordered_algorithm::UnorderedAlgorithm;
+use crate::dy::algorithm::SchedulerAlgorithm;
 use crate::dy::registry::Registry;
 
 // main algorithm for scheduling in Wheel
-pub(crate) type Algorithm<'a> = UnorderedAlgorithm<Registry<'a>>;
\ No newline at end of file
+pub(crate) type Algorithm<'a> = SchedulerAlgorithm<Registry<'a>>;
\ No newline at end of file
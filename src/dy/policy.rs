@@ -0,0 +1,187 @@
+use alloc::collections::{BTreeSet, VecDeque};
+use alloc::vec::Vec;
+use core::fmt::{Debug, Formatter};
+use core::mem::swap;
+use core::ops::Bound;
+use crate::dy::{Deadline, IdNum, SpawnParams, State};
+use crate::dy::algorithm::TaskKey;
+
+/// Strategy used by [`Wheel`](struct.Wheel.html) to decide which task should be polled next.
+///
+/// Wheel executes tasks in *beats*. At the beginning of each beat the wheel repeatedly asks
+/// policy for [`next`](#tymethod.next) task to poll, until policy returns `None` which ends the beat.
+/// If no task was polled during beat, wheel assumes that all tasks are waiting for some external
+/// event and puts itself to sleep until any task is woken.
+///
+/// Policy only decides about order of execution, wheel itself manages task states. If `next` returns
+/// identifier of task that cannot be polled at the moment (e.g it's suspended or waiting) then
+/// this task is skipped. Note that policy must eventually end each beat by returning `None`,
+/// otherwise wheel will loop forever.
+///
+/// Policies are passed to [`Wheel::with_policy`](struct.Wheel.html#method.with_policy). Methods of
+/// policy are never called while other method of policy is executing, but they can be called from
+/// inside tasks (e.g by [`WheelHandle::set_deadline`](struct.WheelHandle.html#method.set_deadline)).
+/// Policy must not interact with wheel handles inside them.
+///
+/// # Examples
+/// Policy that executes tasks in reverse order of spawning.
+/// ```
+/// use juggle::{*, dy::*};
+/// use std::collections::BTreeSet;
+///
+/// #[derive(Default)]
+/// struct Reversed{ tasks: BTreeSet<IdNum>, cursor: Option<IdNum> }
+///
+/// impl SchedulingPolicy for Reversed {
+///     fn insert(&mut self, id: IdNum, _: &SpawnParams) { self.tasks.insert(id); }
+///     fn remove(&mut self, id: IdNum) { self.tasks.remove(&id); }
+///     fn next(&mut self, ctx: &PolicyContext<'_>) -> Option<IdNum> {
+///         let cursor = self.cursor;
+///         self.cursor = self.tasks.iter().rev().copied()
+///             .filter(|&id| cursor.map(|c| id < c).unwrap_or(true))
+///             .find(|&id| ctx.is_pollable(id));
+///         self.cursor
+///     }
+/// }
+///
+/// let wheel = Wheel::with_policy(Reversed::default());
+/// wheel.handle().spawn_default(async {/*...*/}).unwrap();
+/// smol::block_on(wheel).unwrap();
+/// ```
+pub trait SchedulingPolicy {
+    /// Called when new task is registered in wheel.
+    fn insert(&mut self, id: IdNum, params: &SpawnParams);
+    /// Called when task is removed from wheel, because it finished or was cancelled.
+    fn remove(&mut self, id: IdNum);
+    /// Returns next task to poll in current beat or `None` if this beat should end.
+    fn next(&mut self, ctx: &PolicyContext<'_>) -> Option<IdNum>;
+    /// Called right after task returned by [`next`](#tymethod.next) was polled. Default
+    /// implementation does nothing.
+    fn polled(&mut self, _id: IdNum) {}
+    /// Change deadline of task with given id. Returns true if deadline was changed. Default
+    /// implementation ignores deadlines and returns false.
+    fn set_deadline(&mut self, _id: IdNum, _deadline: Deadline) -> bool { false }
+    /// Returns number of deadlines missed by task with given id or `None` if this policy doesn't
+    /// track deadlines. Default implementation returns `None`.
+    fn missed_deadlines(&self, _id: IdNum) -> Option<usize> { None }
+    /// Change weight of task with given id. Returns true if weight was changed. Default
+    /// implementation ignores weights and returns false.
+    fn set_weight(&mut self, _id: IdNum, _weight: u16) -> bool { false }
+}
+
+pub(crate) trait TaskQuery {
+    fn task_state(&self, key: TaskKey) -> State;
+}
+
+/// Provides [`SchedulingPolicy`](trait.SchedulingPolicy.html) with information about state of tasks
+/// in wheel.
+pub struct PolicyContext<'a> {
+    query: &'a dyn TaskQuery,
+}
+
+impl<'a> PolicyContext<'a> {
+    pub(crate) fn new(query: &'a dyn TaskQuery) -> Self { Self { query } }
+    /// Get state of task with given id.
+    pub fn state(&self, id: IdNum) -> State { self.query.task_state(id.to_usize()) }
+    /// Checks if task with given id can be polled now. Equivalent to checking if
+    /// [`state`](#method.state) is `Runnable` or `Cancelling`.
+    pub fn is_pollable(&self, id: IdNum) -> bool {
+        match self.state(id) {
+            State::Runnable | State::Cancelling => true,
+            _ => false,
+        }
+    }
+}
+
+impl Debug for PolicyContext<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str("PolicyContext")
+    }
+}
+
+/// Round-robin policy that polls tasks in first-in first-out order.
+///
+/// New and woken tasks are added at the end of queue. Each task is polled at most once per beat.
+/// Tasks that are blocked by some async event are moved to separate list and are checked at the
+/// beginning of each beat, when woken they are added at the end of queue.
+#[derive(Default, Debug)]
+pub struct FifoPolicy {
+    runnable: VecDeque<IdNum>, //tasks left to visit in current beat
+    next: VecDeque<IdNum>, //tasks to visit in next beat
+    deferred: Vec<IdNum>, //tasks that weren't pollable when visited
+    in_beat: bool,
+}
+
+impl FifoPolicy {
+    /// Create new instance of this policy.
+    pub fn new() -> Self { Self::default() }
+}
+
+impl SchedulingPolicy for FifoPolicy {
+    fn insert(&mut self, id: IdNum, _: &SpawnParams) {
+        self.next.push_back(id);
+    }
+    fn remove(&mut self, id: IdNum) {
+        if let Some(pos) = self.runnable.iter().position(|&k| k == id) {
+            self.runnable.remove(pos);
+        } else if let Some(pos) = self.next.iter().position(|&k| k == id) {
+            self.next.remove(pos);
+        } else if let Some(pos) = self.deferred.iter().position(|&k| k == id) {
+            self.deferred.remove(pos);
+        }
+    }
+    fn next(&mut self, ctx: &PolicyContext<'_>) -> Option<IdNum> {
+        if !self.in_beat { //beginning of new beat
+            self.in_beat = true;
+            swap(&mut self.runnable, &mut self.next);
+            let runnable = &mut self.runnable;
+            self.deferred.retain(|&id| {
+                if ctx.is_pollable(id) {
+                    runnable.push_back(id); //woken tasks go at the end of queue
+                    false
+                } else { true }
+            });
+        }
+        while let Some(id) = self.runnable.pop_front() {
+            if ctx.is_pollable(id) {
+                self.next.push_back(id);
+                return Some(id);
+            }
+            self.deferred.push(id);
+        }
+        self.in_beat = false;
+        None
+    }
+}
+
+/// Policy that polls tasks in order of their identifiers, ignoring order of spawning or waking.
+///
+/// In each beat all runnable tasks are polled once, starting from lowest identifier. This is
+/// default policy of [`Wheel`](struct.Wheel.html).
+#[derive(Default, Debug)]
+pub struct UnorderedPolicy {
+    tasks: BTreeSet<IdNum>,
+    cursor: Option<IdNum>,
+}
+
+impl UnorderedPolicy {
+    /// Create new instance of this policy.
+    pub fn new() -> Self { Self::default() }
+}
+
+impl SchedulingPolicy for UnorderedPolicy {
+    fn insert(&mut self, id: IdNum, _: &SpawnParams) {
+        self.tasks.insert(id);
+    }
+    fn remove(&mut self, id: IdNum) {
+        self.tasks.remove(&id);
+    }
+    fn next(&mut self, ctx: &PolicyContext<'_>) -> Option<IdNum> {
+        let start = match self.cursor {
+            Some(c) => Bound::Excluded(c),
+            None => Bound::Unbounded,
+        };
+        self.cursor = self.tasks.range((start, Bound::Unbounded)).copied().find(|&id| ctx.is_pollable(id));
+        self.cursor
+    }
+}
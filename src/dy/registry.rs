@@ -38,9 +38,6 @@ impl<T> Drop for BorrowRef<'_,T>{
 }
 
 impl<'future> Registry<'future>{
-    #[cfg(debug_assertions)]
-    const SENTINEL: usize = usize::MAX; //so that incrementing it would overflow
-
     #[inline]
     pub fn new()->Self{
         Self{
@@ -64,10 +61,6 @@ impl<'future> Registry<'future>{
             fn next(&mut self) -> Option<Self::Item> { self.0.next() }
         }
 
-        if self.iterate_flag.get() == Self::SENTINEL { //check if we're not retaining
-            panic!("Registry: Cannot iterate during retain operation.");
-        }
-
         self.iterate_flag.set(self.iterate_flag.get() + 1);//we will return borrow from this function
 
         //SAFETY we just inserted iterate flag into iterator, and it will decrement it on drop
@@ -84,35 +77,6 @@ impl<'future> Registry<'future>{
         return unsafe{ (&*self.slab.get()).iter() };
     }
 
-    #[cfg(debug_assertions)]
-    #[inline(always)]
-    fn guard_retain<'b>(&'b self)->impl Drop + 'b{
-        if self.borrow_flag.get() != 0 || self.iterate_flag.get() != 0 {
-            panic!("Registry: Cannot remove tasks that might be borrowed.");
-        }
-        struct Guard<'a>(&'a Cell<usize>, &'a Cell<usize>);
-        impl Drop for Guard<'_>{
-            fn drop(&mut self) {
-                self.0.set(0); //set 0 to both flags as it was before setting it to SENTINEL
-                self.1.set(0);
-            }
-        }
-        //both flags are 0 at this point
-        self.borrow_flag.set(Self::SENTINEL); //lock both flags before returning guard
-        self.iterate_flag.set(Self::SENTINEL);
-        Guard(&self.borrow_flag,&self.iterate_flag)
-    }
-    /// Note that when iterating elements, the count is not updated. Its affected only after iteration is
-    /// done.
-    pub fn retain(&self,func: impl FnMut(TaskKey,&DynamicFuture<'future>)->bool){
-        #[cfg(debug_assertions)]
-        let _guard = self.guard_retain();
-
-        //SAFETY: we check for borrow and iteration in 'guard_retain' lock all flags and create
-        //guard that restores flags on drop so if 'func' panics, flags are restored.
-        let slab = unsafe{ &mut *self.slab.get() };
-        slab.retain(func);
-    }
 }
 
 
@@ -123,11 +87,6 @@ impl<'future> TaskRegistry<TaskKey> for Registry<'future>{
     type Task = DynamicFuture<'future>;
     #[inline]
     fn get(&self,key: TaskKey)->Option<BorrowRef<Self::Task>>{
-        #[cfg(debug_assertions)]
-        if self.borrow_flag.get() == Self::SENTINEL {
-            panic!("Registry: Cannot borrow task during retain operation.");
-        }
-
         //SAFETY: borrow flag saves state of struct as borrowed
         match unsafe{ &*self.slab.get() }.get(key) {
             None => None, //no borrow needed
@@ -147,11 +106,7 @@ impl<'future> TaskRegistry<TaskKey> for Registry<'future>{
     fn insert(&self,val: Self::Task)->Option<TaskKey>{
         #[cfg(debug_assertions)]
         if self.iterate_flag.get() != 0 {
-            if self.iterate_flag.get() == Self::SENTINEL{
-                panic!("Registry: Cannot insert task during retain operation.");
-            }else{
-                panic!("Registry: Cannot insert task during iteration.");
-            }
+            panic!("Registry: Cannot insert task during iteration.");
         }
         unsafe{
             //SAFETY: We know that chunk slab wont reallocate or change memory of already
@@ -162,24 +117,20 @@ impl<'future> TaskRegistry<TaskKey> for Registry<'future>{
     }
 
     #[inline]
-    fn remove(&self,key: TaskKey)->Option<()>{
+    fn remove(&self,key: TaskKey)->Option<Self::Task>{
         #[cfg(debug_assertions)]
         if self.borrow_flag.get() != 0 || self.iterate_flag.get() != 0 {
-            if self.borrow_flag.get() == Self::SENTINEL {
-                panic!("Registry: Cannot remove task during retain operation.");
-            }else{
-                panic!("Registry: Cannot remove task that might be borrowed.");
-            }
+            panic!("Registry: Cannot remove task that might be borrowed.");
         }
-        //SAFETY: we just checked if anything is borrowed
-        unsafe{ (&mut *self.slab.get()).remove(key).map(|_|()) }
+        //SAFETY: we just checked if anything is borrowed. Task is moved out and returned so it's
+        //dropped by caller after mutable borrow of slab ends.
+        unsafe{ (&mut *self.slab.get()).remove(key) }
     }
 
     #[inline]
     fn count(&self)->usize{
         //SAFETY: this is always safe cause iterators and borrows cannot resize slab and resizing
-        //operations such as insert or remove are not recursive (with exception for retain which
-        //updates count after iterating all tasks).
+        //operations such as insert or remove are not recursive.
         unsafe{ (&*self.slab.get()).len() }
     }
     #[inline]
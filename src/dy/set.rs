@@ -0,0 +1,282 @@
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::rc::Rc;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::fmt::{Debug, Formatter};
+use core::future::Future;
+use core::marker::PhantomData;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+use std::thread::{self, JoinHandle};
+use crate::ambient;
+use crate::dy::{Completion, IdNum, SpawnParams, State, SuspendError, Wheel, WheelHandle};
+use crate::dy::handle::PortableParams;
+use crate::dy::thread::{completion, run_wheel, Inbox};
+
+/// Address of task in [`WheelSet`](struct.WheelSet.html), index of wheel and id of task in it.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct TaskAddr {
+    /// Index of wheel in set.
+    pub wheel: usize,
+    /// Id of task in this wheel.
+    pub id: IdNum,
+}
+
+enum Command<M> {
+    Spawn(Box<dyn FnOnce(&WheelHandle<'static>) + Send>),
+    Cancel(IdNum),
+    Message(IdNum, M),
+    Shutdown,
+}
+
+// messages of tasks in single wheel, provided as context of this wheel
+struct Mailboxes<M> {
+    index: usize,
+    boxes: RefCell<BTreeMap<IdNum, Mailbox<M>>>,
+}
+
+struct Mailbox<M> {
+    messages: VecDeque<M>,
+    waker: Option<Waker>,
+}
+
+impl<M> Mailboxes<M> {
+    // forget messages of removed task, so that they are not received by task that reuses its id
+    fn remove(&self, id: IdNum) {
+        let mailbox = self.boxes.borrow_mut().remove(&id);
+        drop(mailbox); //messages are dropped outside of borrow
+    }
+    fn deliver(&self, id: IdNum, message: M) {
+        let mut boxes = self.boxes.borrow_mut();
+        let mailbox = boxes.entry(id).or_insert_with(|| Mailbox { messages: VecDeque::new(), waker: None });
+        mailbox.messages.push_back(message);
+        let waker = mailbox.waker.take();
+        drop(boxes);
+        if let Some(waker) = waker { waker.wake(); }
+    }
+}
+
+/// Group of [`Wheel`](struct.Wheel.html)s, each running on its own thread, whose tasks can
+/// exchange messages of type `M`.
+///
+/// Every wheel stays single-threaded, tasks never move between wheels. Tasks are addressed by
+/// [`TaskAddr`](struct.TaskAddr.html) and can be controlled from any thread (or task of any wheel)
+/// with [`WheelSetHandle`](struct.WheelSetHandle.html): new tasks can be spawned on given wheel,
+/// tasks can be cancelled and messages can be sent to them. Operations are passed to wheels by
+/// lock-free queues. Task receives messages sent to it with
+/// [`WheelSetHandle::receive`](struct.WheelSetHandle.html#method.receive).
+///
+/// Wheels keep running until [`shutdown`](#method.shutdown) is requested, which cancels all
+/// their tasks. Dropping this struct requests shutdown without waiting for it.
+///
+/// This struct is available only with `std` feature.
+///
+/// # Examples
+/// ```
+/// use juggle::{*, dy::*};
+/// use std::sync::mpsc::channel;
+///
+/// let (addr_tx, addr_rx) = channel();
+/// let (out_tx, out_rx) = channel();
+/// let set = WheelSet::new(2, move |index, handle: &WheelHandle<'static>, set: &WheelSetHandle<u32>| {
+///     if index == 1 {
+///         let (set, addr_tx, out_tx) = (set.clone(), addr_tx.clone(), out_tx.clone());
+///         handle.spawn(SpawnParams::named("doubler"), async move {
+///             addr_tx.send(set.current().unwrap()).unwrap();
+///             while let Some(value) = set.receive().await {
+///                 out_tx.send(value * 2).unwrap();
+///             }
+///         }).unwrap();
+///     }
+/// });
+/// let doubler = addr_rx.recv().unwrap();
+/// let set_handle = set.handle().clone();
+/// let sender = set.handle().spawn_on(0, async move {
+///     set_handle.send(doubler, 21); // message from task of other wheel
+/// });
+/// assert_eq!(sender.wait().map(|addr| addr.wheel), Some(0));
+/// assert_eq!(out_rx.recv().unwrap(), 42);
+/// set.shutdown();
+/// assert!(set.join().iter().all(Result::is_ok));
+/// ```
+pub struct WheelSet<M: Send + 'static = ()> {
+    handle: WheelSetHandle<M>,
+    threads: Vec<JoinHandle<Result<(), SuspendError>>>,
+}
+
+impl<M: Send + 'static> WheelSet<M> {
+    /// Start given number of wheels, each on its own thread. Given closure is called on each
+    /// thread with index of wheel, its handle and handle of this set, before wheel starts executing
+    /// tasks.
+    ///
+    /// # Panics
+    /// Panics if count is zero.
+    pub fn new<F>(count: usize, init: F) -> Self
+        where F: Fn(usize, &WheelHandle<'static>, &WheelSetHandle<M>) + Send + Sync + 'static {
+        assert_ne!(count, 0, "WheelSet must have at least one wheel.");
+        let inboxes = (0..count).map(|_| Arc::new(Inbox::new())).collect();
+        let handle = WheelSetHandle { inboxes };
+        let init = Arc::new(init);
+        let threads = (0..count).map(|index| {
+            let (set, init) = (handle.clone(), init.clone());
+            thread::spawn(move || run_set_wheel(index, set, &*init))
+        }).collect();
+        Self { handle, threads }
+    }
+    /// Returns handle of this set, it can be cloned and sent to other threads.
+    pub fn handle(&self) -> &WheelSetHandle<M> { &self.handle }
+    /// Returns number of wheels in this set.
+    pub fn wheel_count(&self) -> usize { self.handle.wheel_count() }
+    /// Request shutdown of all wheels. All tasks are cancelled and wheel threads finish.
+    pub fn shutdown(&self) {
+        for inbox in self.handle.inboxes.iter() {
+            drop(inbox.push(Command::Shutdown));
+        }
+    }
+    /// Wait until all wheel threads finish and return results of wheels, in order of their indexes.
+    ///
+    /// # Panics
+    /// Panics if any wheel thread panicked.
+    pub fn join(mut self) -> Vec<Result<(), SuspendError>> {
+        let threads = core::mem::replace(&mut self.threads, Vec::new());
+        threads.into_iter().map(|t| t.join().unwrap_or_else(|e| std::panic::resume_unwind(e))).collect()
+    }
+}
+
+fn run_set_wheel<M: Send + 'static, F>(index: usize, set: WheelSetHandle<M>, init: &F) -> Result<(), SuspendError>
+    where F: Fn(usize, &WheelHandle<'static>, &WheelSetHandle<M>) {
+    let wheel = Wheel::new();
+    let provided = wheel.provide(Mailboxes::<M> { index, boxes: RefCell::new(BTreeMap::new()) });
+    debug_assert!(provided.is_none());
+    let handle = wheel.handle().clone();
+    let mailboxes = handle.context::<Mailboxes<M>>().expect("Internal Error: no mailboxes.");
+    let boxes = mailboxes.clone();
+    let forget: Rc<dyn Fn(IdNum)> = Rc::new(move |id| boxes.remove(id)); //kept until wheel finishes
+    wheel.on_remove(&forget);
+    init(index, &handle, &set);
+    let inbox = set.inboxes[index].clone();
+    drop(set);
+    run_wheel(wheel, inbox, "WheelSet", move |command| {
+        match command {
+            Command::Spawn(spawn) => spawn(&handle),
+            Command::Cancel(id) => { handle.cancel(id); }
+            Command::Message(id, message) => if handle.get_state(id).map_or(false, |s| s != State::Inactive) {
+                mailboxes.deliver(id, message);
+            }
+            Command::Shutdown => return false,
+        }
+        true
+    })
+}
+
+impl<M: Send + 'static> Drop for WheelSet<M> {
+    fn drop(&mut self) {
+        if !self.threads.is_empty() {
+            self.shutdown();
+        }
+    }
+}
+
+impl<M: Send + 'static> Debug for WheelSet<M> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("WheelSet").field("wheels", &self.wheel_count()).finish()
+    }
+}
+
+/// Handle used to control tasks of [`WheelSet`](struct.WheelSet.html) from any thread.
+pub struct WheelSetHandle<M = ()> {
+    inboxes: Arc<[Arc<Inbox<Command<M>>>]>,
+}
+
+impl<M: Send + 'static> WheelSetHandle<M> {
+    /// Returns number of wheels in associated set.
+    pub fn wheel_count(&self) -> usize { self.inboxes.len() }
+    /// Spawn given future as task on wheel with given index, with default parameters. Task is
+    /// spawned asynchronously, returned [`Completion`](struct.Completion.html) resolves to address
+    /// of spawned task, or to `None` if there is no such wheel or it has already finished.
+    ///
+    /// Completion can be awaited by task of any wheel, but waiting for it with
+    /// [`Completion::wait`](struct.Completion.html#method.wait) inside task of the same wheel
+    /// blocks that wheel forever.
+    pub fn spawn_on<F>(&self, wheel: usize, future: F) -> Completion<TaskAddr> where F: Future<Output=()> + Send + 'static {
+        self.spawn_on_with(wheel, SpawnParams::default(), future)
+    }
+    /// Spawn given future as task on wheel with given index, with given parameters. Works like
+    /// [`spawn_on`](#method.spawn_on), and completion also resolves to `None` when parameters have
+    /// data that is not `Send`, set by [`SpawnParams::data`](struct.SpawnParams.html#method.data).
+    pub fn spawn_on_with<F>(&self, wheel: usize, params: SpawnParams, future: F) -> Completion<TaskAddr>
+        where F: Future<Output=()> + Send + 'static {
+        let (sender, completion) = completion();
+        let params = match PortableParams::new(params) {
+            Some(params) => params,
+            None => return completion, //sender is dropped, so completion resolves to None
+        };
+        //if command is not delivered, sender is dropped and completion resolves to None
+        self.push(wheel, Command::Spawn(Box::new(move |handle: &WheelHandle<'static>| {
+            let id = handle.spawn(params.into_inner(), future);
+            sender.send(id.map(|id| TaskAddr { wheel, id }));
+        })));
+        completion
+    }
+    /// Cancel task with given address. Returns false if there is no such wheel or it has already
+    /// finished, cancelling is done asynchronously so it's not known if task exists.
+    pub fn cancel(&self, task: TaskAddr) -> bool { self.push(task.wheel, Command::Cancel(task.id)) }
+    /// Send message to task with given address. Returns false if there is no such wheel or it
+    /// has already finished. Message is discarded if task doesn't exist when message arrives.
+    pub fn send(&self, to: TaskAddr, message: M) -> bool { self.push(to.wheel, Command::Message(to.id, message)) }
+    /// Returns address of current task, or `None` if not called inside task of wheel in this set.
+    pub fn current(&self) -> Option<TaskAddr> {
+        ambient::with_current(|alg| {
+            let wheel = alg.context::<Mailboxes<M>>()?.index;
+            alg.get_current().map(|k| TaskAddr { wheel, id: IdNum::from_usize(k) })
+        }).ok().flatten()
+    }
+    /// Receive next message sent to current task. Resolves to `None` if not awaited inside task
+    /// of wheel in this set.
+    pub fn receive(&self) -> Receive<M> { Receive { _phantom: PhantomData } }
+    fn push(&self, wheel: usize, command: Command<M>) -> bool {
+        self.inboxes.get(wheel).map_or(false, |inbox| inbox.push(command).is_ok())
+    }
+}
+
+impl<M> Clone for WheelSetHandle<M> {
+    fn clone(&self) -> Self { Self { inboxes: self.inboxes.clone() } }
+}
+
+impl<M> Debug for WheelSetHandle<M> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("WheelSetHandle").field("wheels", &self.inboxes.len()).finish()
+    }
+}
+
+/// Future returned by [`WheelSetHandle::receive`](struct.WheelSetHandle.html#method.receive).
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Receive<M> {
+    _phantom: PhantomData<fn() -> M>,
+}
+
+impl<M: 'static> Future for Receive<M> {
+    type Output = Option<M>;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let found = ambient::with_current(|alg| Some((alg.get_current()?, alg.context::<Mailboxes<M>>()?)));
+        let (key, mailboxes) = match found {
+            Ok(Some(found)) => found,
+            _ => return Poll::Ready(None),
+        };
+        let mut boxes = mailboxes.boxes.borrow_mut();
+        let mailbox = boxes.entry(IdNum::from_usize(key)).or_insert_with(|| Mailbox { messages: VecDeque::new(), waker: None });
+        match mailbox.messages.pop_front() {
+            Some(message) => Poll::Ready(Some(message)),
+            None => {
+                mailbox.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<M> Debug for Receive<M> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result { f.write_str("Receive") }
+}
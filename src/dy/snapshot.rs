@@ -0,0 +1,107 @@
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use core::fmt::{Debug, Formatter};
+use crate::dy::{IdNum, State};
+
+/// Information about single task registered in [`Wheel`](struct.Wheel.html) at the moment
+/// of taking [`snapshot`](struct.WheelHandle.html#method.snapshot).
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TaskInfo {
+    /// Identifier of this task.
+    pub id: IdNum,
+    /// Name of this task or `None` if task is unnamed.
+    pub name: Option<Cow<'static, str>>,
+    /// State of this task.
+    pub state: State,
+    /// Tags given to this task by [`SpawnParams::tag`](struct.SpawnParams.html#method.tag), in
+    /// order of adding.
+    pub tags: Vec<Cow<'static, str>>,
+    /// True if this task was executing when snapshot was taken.
+    pub current: bool,
+}
+
+/// Plain data copy of [`Wheel`](struct.Wheel.html) content taken at some point in time.
+///
+/// Snapshot does not borrow wheel in any way, it can be freely stored, compared or inspected
+/// while tasks continue to execute. Use [`WheelHandle::snapshot`](struct.WheelHandle.html#method.snapshot)
+/// to obtain it. When feature `serde` is enabled this struct can be serialized.
+///
+/// # Examples
+/// ```
+/// use juggle::{*, dy::*};
+///
+/// let wheel = Wheel::new();
+/// let id1 = wheel.handle().spawn(SpawnParams::named("Dog"), async {/*...*/}).unwrap();
+/// let id2 = wheel.handle().spawn(SpawnParams::suspended(true).tag("io"), async {/*...*/}).unwrap();
+///
+/// let snapshot = wheel.handle().snapshot().unwrap();
+/// assert_eq!(snapshot.tasks.len(), 2);
+/// assert_eq!(snapshot.get(id1).unwrap().name.as_deref(), Some("Dog"));
+/// assert_eq!(snapshot.get(id2).unwrap().state, State::Suspended);
+/// assert_eq!(snapshot.with_tag("io").map(|t| t.id).collect::<Vec<_>>(), vec![id2]);
+/// assert_eq!(snapshot.current, None);
+/// ```
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WheelSnapshot {
+    /// Identifier of task that was executing when snapshot was taken or `None` if snapshot was
+    /// taken outside of any task.
+    pub current: Option<IdNum>,
+    /// All tasks registered in wheel, ordered by their identifiers.
+    pub tasks: Vec<TaskInfo>,
+}
+
+impl WheelSnapshot {
+    /// Returns information about task with given id or `None` if this task wasn't registered
+    /// when snapshot was taken.
+    pub fn get(&self, id: IdNum) -> Option<&TaskInfo> {
+        self.tasks.iter().find(|t| t.id == id)
+    }
+    /// Returns iterator over all tasks that were in given state when snapshot was taken.
+    pub fn with_state(&self, state: State) -> impl Iterator<Item=&TaskInfo> {
+        self.tasks.iter().filter(move |t| t.state == state)
+    }
+    /// Returns iterator over all tasks that had given tag when snapshot was taken.
+    pub fn with_tag<'a>(&'a self, tag: &'a str) -> impl Iterator<Item=&'a TaskInfo> {
+        self.tasks.iter().filter(move |t| t.tags.iter().any(|t| t == tag))
+    }
+
+    pub(crate) fn format(&self, f: &mut Formatter<'_>, name: &str) -> core::fmt::Result {
+        struct DebugTask<'a>(&'a TaskInfo);
+        impl Debug for DebugTask<'_> {
+            fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+                match self.0.name.as_deref() {
+                    Some(s) => write!(f, "0x{:X}:{}", self.0.id.to_usize(), s),
+                    None => write!(f, "0x{:X}", self.0.id.to_usize()),
+                }
+            }
+        }
+        struct DebugState<'a>(&'a WheelSnapshot, State);
+        impl Debug for DebugState<'_> {
+            fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+                let mut buff = self.0.with_state(self.1).map(DebugTask);
+                if let Some(first) = buff.next() { f.debug_list().entry(&first).entries(buff).finish() } else { write!(f, "None") }
+            }
+        }
+
+        writeln!(f, "{}{{", name)?;
+        let span = 10; //length of longest label
+        match self.current.and_then(|id| self.get(id)) {
+            Some(task) => writeln!(f, "{:>s$}: {:?}", "current", DebugTask(task), s = span)?,
+            None => writeln!(f, "{:>s$}: None", "current", s = span)?,
+        }
+        writeln!(f, "{:>s$}: {:?}", "runnable", DebugState(self, State::Runnable), s = span)?;
+        writeln!(f, "{:>s$}: {:?}", "waiting", DebugState(self, State::Waiting), s = span)?;
+        writeln!(f, "{:>s$}: {:?}", "suspended", DebugState(self, State::Suspended), s = span)?;
+        writeln!(f, "{:>s$}: {:?}", "cancelled", DebugState(self, State::Cancelled), s = span)?;
+        writeln!(f, "{:>s$}: {:?}", "cancelling", DebugState(self, State::Cancelling), s = span)?;
+        write!(f, "}}")
+    }
+}
+
+impl IntoIterator for WheelSnapshot {
+    type Item = TaskInfo;
+    type IntoIter = alloc::vec::IntoIter<TaskInfo>;
+    fn into_iter(self) -> Self::IntoIter { self.tasks.into_iter() }
+}
@@ -1,31 +1,59 @@
+use alloc::boxed::Box;
+use core::any::Any;
+use core::cell::Ref;
 use core::task::Poll;
 use crate::dy::dyn_future::TaskName;
+use crate::dy::handle::TaskData;
 use crate::dy::registry::{Registry, BorrowRef};
 
 pub(crate) trait TaskRegistry<K: Copy>{ //generalize registry so that it can hold fixed or dynamic futures
     type Task;
     fn get(&self,key: K)->Option<BorrowRef<Self::Task>>;
     fn insert(&self,val: Self::Task)->Option<K>;
-    fn remove(&self,key: K)->Option<()>;
+    fn remove(&self,key: K)->Option<Self::Task>; //returned task must be dropped after all borrows are released
     fn count(&self)->usize;
     fn capacity(&self)->usize;
 }
 
 #[repr(u8)]
 #[derive(Copy,Clone,Eq,PartialEq,Hash,Debug)]
-pub(crate) enum StopReason{ None,Suspended,Cancelled }
+pub(crate) enum StopReason{ None,Suspended,Cancelled,Cancelling }
 impl StopReason{
-    pub fn is_poll_allowed(self)->bool{ self == Self::None }
+    pub fn is_poll_allowed(self)->bool{ self == Self::None || self == Self::Cancelling }
 }
 
 
 pub(crate) trait TaskWrapper {
 
-    fn get_name(&self) -> &TaskName;
+    fn get_name(&self) -> Ref<'_, TaskName>;
+    /// Returns false if name is currently borrowed.
+    fn set_name(&self, name: TaskName) -> bool;
+    fn get_data(&self) -> Option<&TaskData>;
+    fn get_tags(&self) -> &[&'static str];
     fn get_stop_reason(&self)->StopReason;
     fn set_stop_reason(&self,val: StopReason);
     fn is_runnable(&self) -> bool;
+    /// Mark task as woken, without waking wheel.
+    fn set_woken(&self);
     fn poll_local(&self) -> Poll<()>;
+    /// Future replaced by restarting task, must be dropped after all borrows are released.
+    type Retired;
+    fn is_restartable(&self) -> bool;
+    fn restart(&self) -> Option<Self::Retired>;
+    /// Value passed to task by `resume_with`, taken by task when it's polled.
+    fn replace_resume_value(&self, value: Option<Box<dyn Any>>) -> Option<Box<dyn Any>>;
+    /// Replace future with cleanup future (if any) and mark task as cancelling, returns replaced
+    /// future that must be dropped after all borrows are released.
+    fn start_epilogue(&self) -> Option<Self::Retired>;
+    /// Count poll of cleanup future, returns true when its poll limit is exhausted.
+    fn epilogue_tick(&self) -> bool;
+    /// Returns true if cleanup future was polled as many times as its limit allows.
+    fn is_epilogue_expired(&self) -> bool;
+    /// Set cleanup future run when task is cancelled, returns previous cleanup that must be dropped
+    /// after all borrows are released, or given future back if task is already cancelling.
+    fn set_cleanup(&self, cleanup: Self::Retired) -> Result<Option<Self::Retired>, Self::Retired>;
+    /// Returns true if task can be moved to other wheel.
+    fn is_portable(&self) -> bool;
 
 }
 
@@ -0,0 +1,281 @@
+use alloc::boxed::Box;
+use alloc::collections::VecDeque;
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::fmt::{Debug, Display, Formatter};
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+use crate::dy::{IdNum, SpawnParams, WheelHandle};
+use crate::utils::TimerClock;
+use crate::Yield;
+
+type ChildFuture<'a> = Pin<Box<dyn Future<Output=Result<(), ()>> + 'a>>;
+type Sleep<'a, D> = Box<dyn Fn(D) -> Pin<Box<dyn Future<Output=()> + 'a>> + 'a>;
+
+/// Decides which children of [`Supervisor`](struct.Supervisor.html) are restarted when one of
+/// them fails.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum RestartStrategy {
+    /// Restart only failed child.
+    OneForOne,
+    /// Restart all children.
+    OneForAll,
+    /// Restart failed child and all children added after it.
+    RestForOne,
+}
+
+impl Default for RestartStrategy {
+    fn default() -> Self { RestartStrategy::OneForOne }
+}
+
+/// Error returned by [`Supervisor`](struct.Supervisor.html) when its children failed more times
+/// than allowed by restart intensity.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct RestartLimitError;
+
+impl Display for RestartLimitError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str("Restart intensity of supervisor was exceeded.")
+    }
+}
+
+/// Group of tasks that are automatically restarted when they fail.
+///
+/// Each child is described by spawn parameters and closure that creates its future. Child fails
+/// when its future returns `Err`, then it's restarted with
+/// [`WheelHandle::restart`](struct.WheelHandle.html#method.restart) together with other children
+/// chosen by [`RestartStrategy`](enum.RestartStrategy.html). Child that returns `Ok` is finished
+/// and is never restarted again. Child that is removed from wheel by other means (e.g cancelled
+/// with [`WheelHandle::cancel`](struct.WheelHandle.html#method.cancel)) is treated as finished too.
+///
+/// Restart intensity limits number of restarts within given period of time, when exceeded, all
+/// children are cancelled and supervisor resolves to [`RestartLimitError`](struct.RestartLimitError.html).
+/// Restarts can also be delayed by backoff time. Time is measured with given
+/// [`TimerClock`](../utils/trait.TimerClock.html). By default there is no limit and no delay.
+/// Clock can't wake tasks, so backoff should be given a [`timer`](#method.timer) that sleeps,
+/// otherwise supervisor waits by yielding and is polled in every beat until delay passes.
+///
+/// Supervisor does its work in future returned by [`run`](#method.run), which should be spawned in
+/// the same wheel as children. Dropping this future (e.g by cancelling its task) cancels all
+/// children.
+///
+/// # Examples
+/// ```
+/// use juggle::{*, dy::*, utils::StdTimerClock};
+/// use std::cell::Cell;
+/// use std::time::Duration;
+///
+/// let readings = Cell::new(0);
+/// let wheel = Wheel::new();
+/// let handle = wheel.handle().clone();
+/// let supervisor = Supervisor::new(RestartStrategy::OneForOne, StdTimerClock)
+///     .intensity(3, Duration::from_secs(1))
+///     .child(SpawnParams::named("sensor"), || async {
+///         readings.set(readings.get() + 1);
+///         yield_once!();
+///         Err("sensor disconnected") // driver crashes and is restarted
+///     });
+///
+/// let h = handle.clone();
+/// handle.spawn_default(async move {
+///     assert_eq!(supervisor.run(h).await, Err(RestartLimitError));
+/// }).unwrap();
+/// smol::block_on(wheel).unwrap();
+/// assert_eq!(readings.get(), 4); // first start and 3 restarts
+/// ```
+pub struct Supervisor<'futures, C: TimerClock> where C::Instant: Clone {
+    strategy: RestartStrategy,
+    clock: C,
+    max_restarts: Option<(usize, C::Duration)>,
+    backoff: C::Duration,
+    sleep: Option<Sleep<'futures, C::Duration>>,
+    children: Vec<(SpawnParams, Box<dyn FnMut() -> ChildFuture<'futures> + 'futures>)>,
+}
+
+impl<'futures, C: TimerClock> Supervisor<'futures, C> where C::Instant: Clone {
+    /// Create new supervisor without children, that uses given strategy and clock.
+    pub fn new(strategy: RestartStrategy, clock: C) -> Self {
+        Self {
+            strategy,
+            clock,
+            max_restarts: None,
+            backoff: C::Duration::default(),
+            sleep: None,
+            children: Vec::new(),
+        }
+    }
+    /// Set restart intensity, supervisor gives up when there are more than `max_restarts` restarts
+    /// within `period` of time.
+    pub fn intensity(mut self, max_restarts: usize, period: C::Duration) -> Self {
+        self.max_restarts = Some((max_restarts, period));
+        self
+    }
+    /// Set time to wait after failure of child, before it's restarted.
+    pub fn backoff(mut self, delay: C::Duration) -> Self {
+        self.backoff = delay;
+        self
+    }
+    /// Set function that creates future which resolves after given time, e.g timer of async
+    /// runtime. It's used to wait for backoff without polling supervisor in every beat.
+    pub fn timer<F, R>(mut self, sleep: F) -> Self where F: Fn(C::Duration) -> R + 'futures, R: Future<Output=()> + 'futures {
+        self.sleep = Some(Box::new(move |delay| Box::pin(sleep(delay))));
+        self
+    }
+    /// Add child task with given spawn parameters and closure that creates its future.
+    pub fn child<P, F, R, Er>(mut self, params: P, mut factory: F) -> Self
+        where P: Into<SpawnParams>, F: FnMut() -> R + 'futures, R: Future<Output=Result<(), Er>> + 'futures {
+        let factory = move || {
+            let future = factory();
+            Box::pin(async move { future.await.map_err(|_| ()) }) as ChildFuture<'futures>
+        };
+        self.children.push((params.into(), Box::new(factory)));
+        self
+    }
+    /// Number of children in this supervisor.
+    pub fn child_count(&self) -> usize { self.children.len() }
+
+    /// Spawn all children in wheel of given handle and supervise them.
+    ///
+    /// Returned future resolves to `Ok(())` when all children finish successfully or are removed
+    /// from wheel, or to [`RestartLimitError`](struct.RestartLimitError.html) when restart
+    /// intensity was exceeded.
+    /// Children that couldn't be spawned (e.g because handle is invalid) are ignored.
+    pub fn run<T, E>(self, handle: WheelHandle<'futures, T, E>) -> impl Future<Output=Result<(), RestartLimitError>> + 'futures
+        where C: 'futures, T: 'futures, E: 'futures {
+        let Self { strategy, clock, max_restarts, backoff, sleep, children } = self;
+        let monitor = Rc::new(RefCell::new(Monitor { failures: VecDeque::new(), ids: Vec::new(), waker: None }));
+        //children that finish or are cancelled are noticed when they are removed from wheel
+        let m = Rc::downgrade(&monitor);
+        let hook: Rc<dyn Fn(IdNum)> = Rc::new(move |id| if let Some(monitor) = m.upgrade() {
+            Monitor::removed(&monitor, id);
+        });
+        handle.on_remove(&hook);
+        for (index, (params, mut factory)) in children.into_iter().enumerate() {
+            let m = monitor.clone();
+            let id = handle.spawn_factory(params, move || {
+                let future = factory();
+                let monitor = m.clone();
+                async move {
+                    if future.await.is_err() {
+                        Monitor::failed(&monitor, index);
+                        core::future::pending::<()>().await; //wait for restart
+                    }
+                }
+            });
+            monitor.borrow_mut().ids.push(id);
+        }
+        let children = Children { handle, monitor: monitor.clone(), _hook: hook };
+        async move {
+            let epoch = clock.start();
+            let mut history = VecDeque::new(); //times of recent restarts
+            while let Some(index) = WaitFailure(&monitor).await {
+                if let Some((max, period)) = max_restarts {
+                    let now = clock.stop(epoch.clone());
+                    while history.front().map_or(false, |&t| now - t > period) {
+                        history.pop_front();
+                    }
+                    if history.len() >= max {
+                        return Err(RestartLimitError); //children are cancelled on drop
+                    }
+                    history.push_back(now);
+                }
+                if backoff > C::Duration::default() {
+                    match sleep {
+                        Some(ref sleep) => sleep(backoff).await,
+                        None => {
+                            let start = clock.start();
+                            Yield::yield_while(|| clock.stop(start.clone()) < backoff).await;
+                        }
+                    }
+                }
+                let mut m = monitor.borrow_mut();
+                let restarted = match strategy {
+                    RestartStrategy::OneForOne => index..index + 1,
+                    RestartStrategy::OneForAll => 0..m.ids.len(),
+                    RestartStrategy::RestForOne => index..m.ids.len(),
+                };
+                //failures of restarted children that happened in meantime are outdated
+                m.failures.retain(|i| !restarted.contains(i));
+                //children that were removed have no ids, their ids might be already reused
+                let ids: Vec<_> = restarted.filter_map(|i| m.ids[i]).collect();
+                drop(m);
+                for id in ids {
+                    children.handle.restart(id);
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+impl<C: TimerClock + Debug> Debug for Supervisor<'_, C> where C::Instant: Clone, C::Duration: Debug {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Supervisor").field("strategy", &self.strategy).field("clock", &self.clock)
+            .field("max_restarts", &self.max_restarts).field("backoff", &self.backoff)
+            .field("timer", &self.sleep.is_some()).field("children", &self.children.len()).finish()
+    }
+}
+
+struct Monitor {
+    failures: VecDeque<usize>, //indexes of failed children
+    ids: Vec<Option<IdNum>>, //None when child was removed from wheel
+    waker: Option<Waker>,
+}
+
+impl Monitor {
+    fn failed(this: &RefCell<Self>, index: usize) {
+        let mut this = this.borrow_mut();
+        this.failures.push_back(index);
+        this.wake();
+    }
+    fn removed(this: &RefCell<Self>, id: IdNum) {
+        let mut this = this.borrow_mut();
+        if let Some(slot) = this.ids.iter_mut().find(|slot| **slot == Some(id)) {
+            *slot = None;
+            this.wake();
+        }
+    }
+    fn wake(&mut self) {
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+// resolves to index of next failed child that is still in wheel, or None when all children were removed
+struct WaitFailure<'a>(&'a RefCell<Monitor>);
+
+impl Future for WaitFailure<'_> {
+    type Output = Option<usize>;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut monitor = self.0.borrow_mut();
+        while let Some(index) = monitor.failures.pop_front() {
+            if monitor.ids[index].is_some() {
+                return Poll::Ready(Some(index));
+            }
+        }
+        if monitor.ids.iter().all(Option::is_none) {
+            return Poll::Ready(None);
+        }
+        monitor.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+// cancels children that are still alive when supervisor stops
+struct Children<'futures, T, E> {
+    handle: WheelHandle<'futures, T, E>,
+    monitor: Rc<RefCell<Monitor>>,
+    _hook: Rc<dyn Fn(IdNum)>, //removal hook is active while supervisor runs
+}
+
+impl<T, E> Drop for Children<'_, T, E> {
+    fn drop(&mut self) {
+        let ids: Vec<_> = self.monitor.borrow().ids.iter().flatten().copied().collect();
+        for id in ids {
+            self.handle.cancel(id);
+        }
+    }
+}
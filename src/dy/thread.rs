@@ -0,0 +1,265 @@
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt::{Debug, Formatter};
+use core::future::{poll_fn, Future};
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use std::sync::{Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use crate::block_on;
+use crate::dy::{SpawnParams, SuspendError, Wheel, WheelHandle};
+use crate::dy::handle::PortableParams;
+use crate::utils::{to_waker, AtomicQueue, AtomicWakerRegistry};
+
+type Submission<T> = Box<dyn FnOnce(&WheelHandle<'static, T>) + Send>;
+
+/// [`Wheel`](struct.Wheel.html) running on its own OS thread, controlled from other threads.
+///
+/// Wheel thread is started by [`spawn`](#method.spawn) with closure that receives handle of the
+/// wheel, it can spawn tasks that are not `Send` since they are created on wheel thread. Other
+/// threads can [`submit`](#method.submit) futures that are `Send` and wait for their results. When
+/// no task can make progress, wheel thread is parked until some task is woken.
+///
+/// Wheel keeps running until [`shutdown`](#method.shutdown) is requested (which cancels all tasks),
+/// or until it's stopped by [`exit`](struct.WheelHandle.html#method.exit) or by failed task.
+/// Dropping this controller requests shutdown without waiting for it, use [`join`](#method.join) to
+/// wait for result of the wheel.
+///
+/// This struct is available only with `std` feature.
+///
+/// # Examples
+/// ```
+/// use juggle::{*, dy::*};
+///
+/// let wheel = WheelThread::spawn(|handle: &WheelHandle<'static>| {
+///     handle.spawn_default(async { /* task that is not Send */ }).unwrap();
+/// });
+/// let sum = wheel.submit(async {
+///     Yield::times(3).await;
+///     2 + 2
+/// });
+/// assert_eq!(sum.wait(), Some(4));
+/// wheel.shutdown();
+/// assert_eq!(wheel.join(), Ok(()));
+/// ```
+pub struct WheelThread<T: Send + 'static = ()> {
+    inbox: Arc<Inbox<Request<T>>>,
+    thread: Option<JoinHandle<Result<T, SuspendError>>>,
+}
+
+enum Request<T> {
+    Submit(Submission<T>),
+    Shutdown,
+}
+
+// queue of commands sent to wheel from other threads, received by dedicated task of that wheel
+pub(crate) struct Inbox<C> {
+    commands: AtomicQueue<C>,
+    waker: AtomicWakerRegistry, //waker of task that receives commands
+}
+
+impl<C> Inbox<C> {
+    pub(crate) fn new() -> Self { Self { commands: AtomicQueue::new(), waker: AtomicWakerRegistry::empty() } }
+    // returns given command back if wheel has already finished
+    pub(crate) fn push(&self, command: C) -> Result<(), C> {
+        self.commands.push(command)?;
+        self.waker.notify_wake();
+        Ok(())
+    }
+    pub(crate) fn is_closed(&self) -> bool { self.commands.is_closed() }
+    fn poll_receive(&self, cx: &mut Context<'_>) -> Poll<Vec<C>> {
+        let commands = self.commands.take_all();
+        if !commands.is_empty() {
+            return Poll::Ready(commands);
+        }
+        self.waker.register(cx.waker());
+        if self.commands.is_empty() { Poll::Pending } else {
+            self.waker.clear();
+            Poll::Ready(self.commands.take_all())
+        }
+    }
+}
+
+// Run wheel on current thread until it finishes. Commands from inbox are passed to 'execute' by
+// task with given name, when it returns false all other tasks are cancelled. Thread is parked
+// when no task can make progress. Inbox is closed when wheel finishes, so commands that arrive too
+// late are given back to senders.
+pub(crate) fn run_wheel<T, E, C>(wheel: Wheel<'static, T, E>, inbox: Arc<Inbox<C>>, name: &'static str,
+                                 mut execute: impl FnMut(C) -> bool + 'static) -> Result<T, E>
+    where T: Default + 'static, E: From<SuspendError> + 'static, C: 'static {
+    let handle = wheel.handle().clone();
+    let receiver = inbox.clone();
+    wheel.handle().spawn(SpawnParams::named(name), async move {
+        'receive: loop {
+            for command in poll_fn(|cx| receiver.poll_receive(cx)).await {
+                if !execute(command) {
+                    break 'receive;
+                }
+            }
+        }
+        let me = handle.current();
+        for task in handle.tasks().filter(|t| Some(t.id) != me) {
+            handle.cancel(task.id);
+        }
+    }).expect("Internal Error: cannot spawn receiver task.");
+    let current = thread::current();
+    let waker = to_waker(Arc::new(move || current.unpark()));
+    let result = block_on(wheel, thread::park, &waker);
+    drop(inbox.commands.close()); //commands that arrived too late
+    result
+}
+
+impl<T: Default + Send + 'static> WheelThread<T> {
+    /// Start new wheel on its own thread. Given closure is called on that thread with handle of
+    /// the wheel before it starts executing tasks.
+    pub fn spawn<F>(init: F) -> Self where F: FnOnce(&WheelHandle<'static, T>) + Send + 'static {
+        let inbox = Arc::new(Inbox::new());
+        let inner = inbox.clone();
+        let thread = thread::spawn(move || {
+            let wheel: Wheel<'static, T> = Wheel::new_typed();
+            let handle = wheel.handle().clone();
+            init(&handle);
+            run_wheel(wheel, inner, "WheelThread", move |request| match request {
+                Request::Submit(submission) => {
+                    submission(&handle);
+                    true
+                }
+                Request::Shutdown => false,
+            })
+        });
+        Self { inbox, thread: Some(thread) }
+    }
+}
+
+impl<T: Send + 'static> WheelThread<T> {
+    /// Spawn given future as task of this wheel, with default parameters. Returns
+    /// [`Completion`](struct.Completion.html) that can be used to wait for output of future.
+    pub fn submit<F>(&self, future: F) -> Completion<F::Output>
+        where F: Future + Send + 'static, F::Output: Send + 'static {
+        self.submit_with(SpawnParams::default(), future)
+    }
+    /// Spawn given future as task of this wheel, with given parameters. Works like
+    /// [`submit`](#method.submit), but completion is resolved as cancelled without running future
+    /// when parameters have data that is not `Send`, set by
+    /// [`SpawnParams::data`](struct.SpawnParams.html#method.data).
+    pub fn submit_with<F>(&self, params: SpawnParams, future: F) -> Completion<F::Output>
+        where F: Future + Send + 'static, F::Output: Send + 'static {
+        let (sender, completion) = completion();
+        let params = match PortableParams::new(params) {
+            Some(params) => params,
+            None => return completion, //sender is dropped, so completion is resolved as cancelled
+        };
+        //if wheel has finished, submission is dropped and completion is resolved as cancelled
+        drop(self.inbox.push(Request::Submit(Box::new(move |handle: &WheelHandle<'static, T>| {
+            handle.spawn(params.into_inner(), async move {
+                let value = future.await;
+                sender.send(Some(value));
+            });
+        }))));
+        completion
+    }
+    /// Request shutdown of this wheel. All tasks are cancelled and wheel thread finishes.
+    /// Futures submitted after this call are never executed.
+    pub fn shutdown(&self) { drop(self.inbox.push(Request::Shutdown)); }
+    /// Returns true if wheel thread has finished.
+    pub fn is_finished(&self) -> bool { self.inbox.is_closed() }
+    /// Wait until wheel thread finishes and return result of the wheel.
+    ///
+    /// # Panics
+    /// Panics if wheel thread panicked.
+    pub fn join(mut self) -> Result<T, SuspendError> {
+        let thread = self.thread.take().expect("Internal Error: thread already joined.");
+        thread.join().unwrap_or_else(|e| std::panic::resume_unwind(e))
+    }
+}
+
+impl<T: Send + 'static> Drop for WheelThread<T> {
+    fn drop(&mut self) {
+        if self.thread.is_some() {
+            self.shutdown();
+        }
+    }
+}
+
+impl<T: Send + 'static> Debug for WheelThread<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("WheelThread").field("finished", &self.is_finished()).finish()
+    }
+}
+
+struct Slot<R> {
+    state: Mutex<Option<Option<R>>>, //Some when task finished or was dropped
+    ready: Condvar,
+    waker: AtomicWakerRegistry, //task awaiting completion
+}
+
+// create completion and sender that resolves it
+pub(crate) fn completion<R>() -> (Sender<R>, Completion<R>) {
+    let slot = Arc::new(Slot { state: Mutex::new(None), ready: Condvar::new(), waker: AtomicWakerRegistry::empty() });
+    (Sender(slot.clone()), Completion { slot })
+}
+
+// resolves completion when dropped, so that waiting thread is never blocked forever
+pub(crate) struct Sender<R>(Arc<Slot<R>>);
+
+impl<R> Sender<R> {
+    pub(crate) fn send(&self, value: Option<R>) {
+        let mut state = self.0.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.is_none() {
+            *state = Some(value);
+            self.0.ready.notify_all();
+            drop(state);
+            self.0.waker.notify_wake();
+        }
+    }
+}
+
+impl<R> Drop for Sender<R> {
+    fn drop(&mut self) { self.send(None) }
+}
+
+/// Result of operation passed to other thread, e.g. future submitted to
+/// [`WheelThread`](struct.WheelThread.html). It can be waited for by blocking current thread with
+/// [`wait`](#method.wait), or awaited by task, then it resolves to the same value as `wait`.
+pub struct Completion<R> {
+    slot: Arc<Slot<R>>,
+}
+
+impl<R> Completion<R> {
+    /// Block current thread until submitted future completes. Returns its output or `None` if
+    /// task was cancelled or wheel finished before it completed.
+    pub fn wait(self) -> Option<R> {
+        let mut state = self.slot.state.lock().unwrap_or_else(|e| e.into_inner());
+        loop {
+            if let Some(value) = state.take() {
+                return value;
+            }
+            state = self.slot.ready.wait(state).unwrap_or_else(|e| e.into_inner());
+        }
+    }
+    /// Returns true if submitted future completed, was cancelled or will never be executed.
+    pub fn is_finished(&self) -> bool {
+        self.slot.state.lock().unwrap_or_else(|e| e.into_inner()).is_some()
+    }
+}
+
+impl<R> Future for Completion<R> {
+    type Output = Option<R>;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.slot.state.lock().unwrap_or_else(|e| e.into_inner());
+        match state.take() {
+            Some(value) => Poll::Ready(value),
+            None => {
+                self.slot.waker.register(cx.waker()); //registered under lock, so wake is not missed
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<R> Debug for Completion<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Completion").field("finished", &self.is_finished()).finish()
+    }
+}
@@ -5,8 +5,13 @@ use core::future::Future;
 use core::pin::Pin;
 use core::task::*;
 use super::handle::*;
-use crate::dy::Algorithm;
-use crate::spin_block_on;
+use alloc::boxed::Box;
+use crate::dy::{Algorithm, SchedulingPolicy};
+use crate::dy::algorithm::Outcome;
+use core::marker::PhantomData;
+use crate::dy::registry::Registry;
+use crate::{ambient, spin_block_on};
+use crate::utils::TimerClock;
 
 /// Single-thread async task scheduler with dynamic task state control. Implements `Future`.
 ///
@@ -105,23 +110,63 @@ use crate::spin_block_on;
 ///     smol::block_on(wheel).unwrap(); // or any other utility to block on future.
 /// }
 /// ```
-pub struct Wheel<'futures> {
+///
+/// # Output
+/// Wheel resolves to `Result<T, E>`, where output type `T` is `()` and error type `E` is
+/// [`SuspendError`](struct.SuspendError.html) by default. Custom types can be used with
+/// [`new_typed`](#method.new_typed). Any task can stop the wheel with value of type `T` by calling
+/// [`exit`](struct.WheelHandle.html#method.exit), when all tasks finish without calling it then
+/// wheel resolves to `T::default()`. Tasks spawned by
+/// [`spawn_fallible`](struct.WheelHandle.html#method.spawn_fallible) can stop the wheel with errors
+/// that are converted to `E` (which must be constructible from `SuspendError`).
+pub struct Wheel<'futures, T = (), E = SuspendError> {
     ptr: Rc<Algorithm<'futures>>,
-    handle: WheelHandle<'futures>,
+    handle: WheelHandle<'futures, T, E>,
 }
 
 /// Same as [`Wheel`](struct.Wheel.html) except that it has fixed content and there is no way to
 /// control state of tasks within it. Implements `Future`.
-pub struct LockedWheel<'futures> {
+pub struct LockedWheel<'futures, T = (), E = SuspendError> {
     alg: Algorithm<'futures>,
+    _phantom: PhantomData<fn() -> (T, E)>,
 }
 
 impl<'futures> Wheel<'futures> {
     /// Create new instance
     pub fn new() -> Self {
+        Self::new_typed()
+    }
+
+    /// Create new instance that uses given [`SchedulingPolicy`](trait.SchedulingPolicy.html) to
+    /// decide in which order tasks are polled. Instances created with [`new`](#method.new) use
+    /// [`UnorderedPolicy`](struct.UnorderedPolicy.html).
+    ///
+    /// # Examples
+    /// ```
+    /// use juggle::{*, dy::*};
+    ///
+    /// let wheel = Wheel::with_policy(FifoPolicy::new());
+    /// wheel.handle().spawn_default(async {/*...*/}).unwrap();
+    /// smol::block_on(wheel).unwrap();
+    /// ```
+    pub fn with_policy(policy: impl SchedulingPolicy + 'static) -> Self {
+        Self::with_policy_typed(policy)
+    }
+}
+
+impl<'futures, T, E> Wheel<'futures, T, E> {
+    /// Create new instance with custom output and error types. Same as [`new`](#method.new) for
+    /// wheels with default types.
+    pub fn new_typed() -> Self {
         Self::from_inner(Algorithm::new())
     }
 
+    /// Create new instance with custom output and error types that uses given scheduling policy.
+    /// Same as [`with_policy`](#method.with_policy) for wheels with default types.
+    pub fn with_policy_typed(policy: impl SchedulingPolicy + 'static) -> Self {
+        Self::from_inner(Algorithm::with(Registry::default(), Box::new(policy)))
+    }
+
     fn from_inner(alg: Algorithm<'futures>) -> Self {
         let ptr = Rc::new(alg);
         let handle = WheelHandle::new(Rc::downgrade(&ptr));
@@ -133,7 +178,7 @@ impl<'futures> Wheel<'futures> {
     /// Any interaction with `Wheel`'s content is done by handles. Handles works as reference
     /// counted pointers, they can be cloned to use inside tasks but cannot be shared
     /// between threads.
-    pub fn handle(&self) -> &WheelHandle<'futures> { &self.handle }
+    pub fn handle(&self) -> &WheelHandle<'futures, T, E> { &self.handle }
 
     /// Lock this wheel preventing all handles from affecting the tasks.
     ///
@@ -143,68 +188,205 @@ impl<'futures> Wheel<'futures> {
     /// # Panics
     /// Panics if this method was called inside handle's method such as
     /// [`with_name`](struct.WheelHandle.html#method.with_name).
-    pub fn lock(self) -> LockedWheel<'futures> {
+    pub fn lock(self) -> LockedWheel<'futures, T, E> {
         // rc has always strong count of 1 (it can have strong count > 1 during calls
         // on handle, but if these calls return then it will be back to 1)
         let alg = Rc::try_unwrap(self.ptr).ok().expect("Cannot lock inside call to handle's method.");
-        LockedWheel { alg }
+        LockedWheel { alg, _phantom: PhantomData }
+    }
+
+    /// Returns future that drives tasks of this wheel until given future completes.
+    ///
+    /// Returned future resolves to output of given future. Given future is polled between beats
+    /// of this wheel, it doesn't become task in wheel. Tasks that didn't finish until then remain
+    /// registered in this wheel, so that it can be used again afterwards. Similar to
+    /// `LocalSet::run_until` from tokio.
+    ///
+    /// When wheel is stopped by [`exit`](struct.WheelHandle.html#method.exit) or by failed task,
+    /// all tasks are cancelled and given future keeps being polled alone until it completes. Tasks
+    /// are not polled anymore and output of the wheel is kept until wheel is awaited. Given future
+    /// should therefore observe that tasks it waits for were dropped, e.g by closed
+    /// [`channel`](../channel/index.html).
+    ///
+    /// # Panics
+    /// Returned future panics when polled inside task of this wheel.
+    ///
+    /// # Examples
+    /// ```
+    /// use juggle::{*, dy::*};
+    /// use std::cell::Cell;
+    ///
+    /// let progress = Cell::new(0);
+    /// let wheel = Wheel::new();
+    /// wheel.handle().spawn_default(async {
+    ///     loop {
+    ///         progress.set(progress.get() + 1);
+    ///         yield_once!();
+    ///     }
+    /// }).unwrap();
+    /// let result = smol::block_on(wheel.run_until(async {
+    ///     yield_while!(progress.get() < 10);
+    ///     "done"
+    /// }));
+    /// assert_eq!(result, "done");
+    /// assert_eq!(wheel.handle().registered_count(), 1); // background task is still registered
+    /// ```
+    pub fn run_until<F: Future>(&self, future: F) -> RunUntil<'_, 'futures, F, T, E> {
+        RunUntil { wheel: self, future }
+    }
+
+    /// Poll tasks in this wheel until given time budget is used up.
+    ///
+    /// Time is measured with given [`TimerClock`](../utils/trait.TimerClock.html) and checked before
+    /// each task is polled, so this method never starts polling new task after budget is used up
+    /// (but it can't interrupt task that is already executing). Method returns early when there
+    /// is no task that can be polled (all tasks are waiting, suspended or finished). Returns true if
+    /// some work remains, that is when there are still runnable tasks in wheel.
+    ///
+    /// When wheel is stopped by [`exit`](struct.WheelHandle.html#method.exit) or by task that failed
+    /// with [`ErrorPolicy::StopWheel`](enum.ErrorPolicy.html#variant.StopWheel), all tasks are
+    /// cancelled and this method returns false. Output of the wheel (exit value or error) is kept
+    /// until wheel is awaited, further calls of this method don't poll anything.
+    ///
+    /// Calling this method repeatedly continues where previous call stopped, so that scheduling
+    /// policy keeps order of tasks (e.g round-robin fairness) across calls.
+    ///
+    /// # Examples
+    /// ```
+    /// use juggle::{*, dy::*, utils::StdTimerClock};
+    /// use std::time::Duration;
+    ///
+    /// let wheel = Wheel::new();
+    /// wheel.handle().spawn_default(Yield::times(1000)).unwrap();
+    /// # fn control_routine(){}
+    /// while wheel.poll_for(Duration::from_millis(1), &StdTimerClock) {
+    ///     control_routine(); // executed at least every 1ms (if tasks yield often enough)
+    /// }
+    /// ```
+    pub fn poll_for<C: TimerClock>(&self, budget: C::Duration, clock: &C) -> bool where C::Instant: Clone {
+        let start = clock.start();
+        ambient::enter(&self.ptr, || self.ptr.poll_for(|| clock.stop(start.clone()) >= budget))
     }
 
-    pub fn spin_block(self)->Result<(),SuspendError>{ spin_block_on(self) }
-    pub fn spin_block_forever(self)->!{
+    /// Provide value to all tasks of this wheel, it can be accessed by
+    /// [`WheelHandle::context`](struct.WheelHandle.html#method.context) or
+    /// [`WheelHandle::with_context`](struct.WheelHandle.html#method.with_context).
+    ///
+    /// Wheel stores at most one value of each type, returns previous value of the same type if
+    /// there was any. Values are dropped together with this wheel.
+    ///
+    /// # Examples
+    /// ```
+    /// use juggle::{*, dy::*};
+    ///
+    /// struct Config { retries: u32 }
+    ///
+    /// let wheel = Wheel::new();
+    /// wheel.provide(Config { retries: 3 });
+    /// let handle = wheel.handle().clone();
+    /// wheel.handle().spawn_default(async move {
+    ///     let retries = handle.with_context(|c: &Config| c.retries).unwrap();
+    ///     assert_eq!(retries, 3);
+    /// }).unwrap();
+    /// smol::block_on(wheel).unwrap();
+    /// ```
+    pub fn provide<C: 'static>(&self, value: C) -> Option<Rc<C>> { self.ptr.provide(value) }
+    // call given function with id of each task removed from this wheel, while hook is alive
+    pub(crate) fn on_remove(&self, hook: &Rc<dyn Fn(IdNum)>) { self.ptr.on_remove(hook) }
+
+    pub(crate) fn poll_beat(&self, cx: &mut Context<'_>) -> Poll<Outcome> {
+        ambient::enter(&self.ptr, || self.ptr.poll_beat(cx))
+    }
+
+    pub fn spin_block(self)->Result<T,E> where T: Default + 'static, E: From<SuspendError> + 'static { spin_block_on(self) }
+    pub fn spin_block_forever(self)->! where T: Default + 'static, E: From<SuspendError> + Debug + 'static {
         self.spin_block().unwrap();
         panic!("Wheel::spin_block_forever(): Didn't expect all tasks to finish.")
     }
 }
 
-impl<'futures> LockedWheel<'futures> {
+impl<'futures, T, E> LockedWheel<'futures, T, E> {
     /// Unlock this wheel so that a handle can be obtained and used to spawn or control tasks.
     ///
     /// Transforms this instance back to [`Wheel`](struct.Wheel.html)
     /// Note that handles which were invalidated after this wheel was locked won't be valid
     /// again after calling this method, new [`handle`](struct.Wheel.html#method.handle) should be obtained.
-    pub fn unlock(self) -> Wheel<'futures> {
+    pub fn unlock(self) -> Wheel<'futures, T, E> {
         Wheel::from_inner(self.alg)
     }
 
-    pub fn spin_block(self)->Result<(),SuspendError>{ spin_block_on(self) }
-    pub fn spin_block_forever(self)->!{
+    pub fn spin_block(self)->Result<T,E> where T: Default + 'static, E: From<SuspendError> + 'static { spin_block_on(self) }
+    pub fn spin_block_forever(self)->! where T: Default + 'static, E: From<SuspendError> + Debug + 'static {
         self.spin_block().unwrap();
         panic!("LockedWheel::spin_block_forever(): Didn't expect all tasks to finish.")
     }
 }
 
-impl<'futures> Debug for Wheel<'futures> {
+impl<'futures, T, E> Debug for Wheel<'futures, T, E> {
     fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         self.ptr.format_internal(f, "Wheel")
     }
 }
 
-impl<'futures> Debug for LockedWheel<'futures> {
+impl<'futures, T, E> Debug for LockedWheel<'futures, T, E> {
     fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         self.alg.format_internal(f, "LockedWheel")
     }
 }
 
-impl<'futures> Default for Wheel<'futures> {
-    fn default() -> Self { Self::new() }
+impl<'futures, T, E> Default for Wheel<'futures, T, E> {
+    fn default() -> Self { Self::new_typed() }
+}
+
+
+fn map_outcome<T: Default + 'static, E: From<SuspendError> + 'static>(outcome: Outcome) -> Result<T, E> {
+    match outcome {
+        Outcome::Finished => Ok(T::default()),
+        Outcome::Exited(value) => Ok(*value.downcast::<T>().ok().expect("Internal Error: unexpected output type.")),
+        Outcome::Suspended => Err(SuspendError.into()),
+        Outcome::Failed(error) => Err(*error.downcast::<E>().ok().expect("Internal Error: unexpected error type.")),
+    }
 }
 
+impl<'futures, T: Default + 'static, E: From<SuspendError> + 'static> Future for Wheel<'futures, T, E> {
+    type Output = Result<T, E>;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let alg = &self.as_ref().ptr;
+        ambient::enter(alg, || alg.poll_internal(cx)).map(map_outcome)
+    }
+}
 
-impl<'futures> Future for Wheel<'futures> {
-    type Output = Result<(), SuspendError>;
+impl<'futures, T: Default + 'static, E: From<SuspendError> + 'static> Future for LockedWheel<'futures, T, E> {
+    type Output = Result<T, E>;
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        self.as_ref().ptr.poll_internal(cx).map(|flag| if flag { Ok(()) } else { Err(SuspendError) })
+        let alg = &self.as_ref().alg;
+        ambient::enter(alg, || alg.poll_internal(cx)).map(map_outcome)
     }
 }
 
-impl<'futures> Future for LockedWheel<'futures> {
-    type Output = Result<(), SuspendError>;
+/// Future returned by [`Wheel::run_until`](struct.Wheel.html#method.run_until).
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct RunUntil<'a, 'futures, F: Future, T = (), E = SuspendError> {
+    wheel: &'a Wheel<'futures, T, E>,
+    future: F,
+}
+
+impl<F: Future, T, E> Future for RunUntil<'_, '_, F, T, E> {
+    type Output = F::Output;
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        self.as_ref().alg.poll_internal(cx).map(|flag| if flag { Ok(()) } else { Err(SuspendError) })
+        //SAFETY: future is never moved out of pinned struct
+        let this = unsafe { self.get_unchecked_mut() };
+        let future = &mut this.future;
+        let alg = &this.wheel.ptr;
+        ambient::enter(alg, || alg.poll_until(cx, |cx| unsafe { Pin::new_unchecked(&mut *future) }.poll(cx)))
     }
 }
 
+impl<F: Future, T, E> Debug for RunUntil<'_, '_, F, T, E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("RunUntil").field("wheel", self.wheel).finish()
+    }
+}
 
 /// Error returned by scheduler's `Future` when all tasks become suspended.
 ///
@@ -80,8 +80,11 @@ extern crate alloc;
 pub mod utils;
 pub mod dy;
 pub mod st;
+pub mod channel;
+pub mod ambient;
 mod yield_helper;
 mod block;
+mod budget;
 
 #[doc(hidden)]
 pub mod macro_private{
@@ -102,6 +105,7 @@ pub mod macro_private{
 
 pub use self::block::{block_on, spin_block_on};
 pub use self::yield_helper::{Yield, YieldTimes, YieldWhile};
+pub use self::budget::{consume_budget, poll_budget, remaining_budget, ConsumeBudget, POLL_BUDGET};
 
 
 
@@ -1,7 +1,11 @@
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+use core::any::Any;
 use core::ops::Index;
 use crate::st::stt_future::StaticFuture;
 use core::cell::{Cell, UnsafeCell};
-use crate::utils::{AtomicWakerRegistry, Ucw, DropGuard};
+use crate::utils::{AtomicWakerRegistry, Ucw, DropGuard, ContextMap};
+use crate::dy::idle::{IdleSlot, IdleWaiters};
 use crate::dy::algorithm::TaskKey;
 use core::task::{Context, Poll};
 use crate::st::handle::StaticHandle;
@@ -11,7 +15,8 @@ use core::mem::forget;
 use core::sync::atomic::AtomicUsize;
 use core::sync::atomic::Ordering::Relaxed;
 use crate::dy::State;
-use std::sync::atomic::Ordering::{Acquire, Release};
+use core::sync::atomic::Ordering::{Acquire, Release};
+use crate::budget::with_budget;
 
 pub(crate) struct StaticAlgorithm{
     registry: &'static [StaticFuture],
@@ -20,6 +25,8 @@ pub(crate) struct StaticAlgorithm{
     suspended_count: Cell<usize>,
     unfinished_count: Cell<usize>,
     current_generation: AtomicUsize,
+    idle: IdleWaiters,
+    context: ContextMap,
 }
 
 impl StaticAlgorithm{
@@ -32,6 +39,8 @@ impl StaticAlgorithm{
             suspended_count: Cell::new(0),
             unfinished_count: Cell::new(usize::MAX), //uninit mark
             current_generation: AtomicUsize::new(0),
+            idle: IdleWaiters::new(),
+            context: ContextMap::new(),
         }
     }
     pub(crate) fn init(&'static self){ //create all self-refs
@@ -58,6 +67,7 @@ impl StaticAlgorithm{
     }
     pub(crate) fn dispose(&'static self){
         let _guard = DropGuard::new(move||{
+            self.context.clear(); //after tasks are cleaned up, they might use context in destructors
             //invalidate all handles
             //only need to be volatile increment, this can't be concurrent
             //todo changed to acq/rel cause it should prevent bugs when multiple threads will see unmodified handle
@@ -79,6 +89,9 @@ impl StaticAlgorithm{
         }
     }
 
+    pub(crate) fn provide<C: 'static>(&self, value: C) -> Option<Rc<C>> { self.context.provide(value) }
+    pub(crate) fn context<C: 'static>(&self) -> Option<Rc<C>> { self.context.get() }
+
     pub(crate) fn get_generation(&self)->usize {
         self.current_generation.load(Relaxed) //only volatile read cause it might be read from concurrent threads
     }
@@ -106,6 +119,19 @@ impl StaticAlgorithm{
         }
     }
 
+    pub(crate) fn resume_with(&self, key: TaskKey, value: Box<dyn Any>) -> bool {
+        match self.registry.get(key) {
+            Some(task) if task.get_stop_reason() == StopReason::Suspended => {
+                drop(task.replace_resume_value(Some(value)));
+                self.resume(key)
+            }
+            _ => false,
+        }
+    }
+    pub(crate) fn take_resume_value(&self, key: TaskKey) -> Option<Box<dyn Any>> {
+        self.registry.get(key).and_then(|task| task.replace_resume_value(None))
+    }
+
     //if rotate_once encounters suspended task, then it will be removed from queue
     pub(crate) fn suspend(&self, key: TaskKey) -> bool {
         match self.registry.get(key) {
@@ -132,7 +158,8 @@ impl StaticAlgorithm{
                 match task.get_stop_reason() {
                     //if restart suspended, then change to just restart
                     StopReason::Suspended | StopReason::RestartSuspended => self.dec_suspended(),
-                    StopReason::Cancelled | StopReason::Finished => self.inc_unfinished(),
+                    //cancelled task is still counted as unfinished until it's processed in beat
+                    StopReason::Finished => self.inc_unfinished(),
                     StopReason::Restart => return true,//todo decide if user should know about restarting state cause its only market state
                     _ => {}
                 }
@@ -143,10 +170,24 @@ impl StaticAlgorithm{
         }
     }
 
+    // restart siblings of automatically restarted task, finished siblings are counted as unfinished
+    // again while cancelled ones are still counted
+    fn restart_group(&self, key: TaskKey) {
+        let group = match self.registry[key].get_restart_group() {
+            Some(group) => group,
+            None => return,
+        };
+        for (k, task) in self.registry.iter().enumerate() {
+            if k != key && task.get_restart_group() == Some(group) {
+                self.restart(k);
+            }
+        }
+    }
+
     pub(crate) fn cancel(&self, key: TaskKey) -> bool {
         if let Some(task) = self.registry.get(key) {
             let r = task.get_stop_reason();
-            if r != StopReason::Cancelled {
+            if r != StopReason::Cancelled && r != StopReason::Finished {
                 task.set_stop_reason(StopReason::Cancelled);
                 if r == StopReason::Suspended {
                     self.dec_suspended();
@@ -157,6 +198,10 @@ impl StaticAlgorithm{
         false
     }
 
+    pub(crate) fn poll_idle(&self, slot: &mut Option<IdleSlot>, cx: &mut Context<'_>) -> Poll<()> {
+        self.idle.poll_wait(slot, cx)
+    }
+
     pub(crate) fn get_state(&self, key: TaskKey) -> State {
         match self.registry.get(key) {
             Some(task) => match task.get_stop_reason() {
@@ -204,7 +249,15 @@ impl StaticAlgorithm{
                 StopReason::None => false, //don't skip
                 StopReason::Cancelled => {
                     run_task.cancel(StaticHandle::with_id(self,usize::MAX),false);
-                    self.dec_unfinished();
+                    if run_task.auto_restart(true) {
+                        //restarted in next beat, task is still unfinished
+                        run_task.set_stop_reason(StopReason::Restart);
+                        self.restart_group(run_key);
+                    } else {
+                        //mark as finished so that it's not counted again in next beat
+                        run_task.set_stop_reason(StopReason::Finished);
+                        self.dec_unfinished();
+                    }
                     continue; //task cancelled nothing to do
                 }
                 StopReason::Restart => {
@@ -229,13 +282,21 @@ impl StaticAlgorithm{
             // be careful with interior mutability types here cause 'poll_local' can invoke any method
             // on handle
             any_poll = true;
-            let is_ready = run_task.poll_local(StaticHandle::with_id(self,gen_id),restart).is_ready(); //run user code
+            let is_ready = with_budget(|| run_task.poll_local(StaticHandle::with_id(self,gen_id),restart)).is_ready(); //run user code
             drop(guard);
             if is_ready { //task was finished and dropped, mark it
-                run_task.set_stop_reason(StopReason::Finished);
-                self.dec_unfinished();//one less
+                if run_task.get_stop_reason() == StopReason::None && run_task.auto_restart(false) {
+                    run_task.set_stop_reason(StopReason::Restart); //restart in next beat
+                    self.restart_group(run_key);
+                } else if run_task.get_stop_reason() != StopReason::Restart {
+                    run_task.set_stop_reason(StopReason::Finished);
+                    self.dec_unfinished();//one less
+                }
             }
         }
+        if !any_poll {
+            self.idle.notify_idle();
+        }
         any_poll
     }
 }
\ No newline at end of file
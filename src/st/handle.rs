@@ -1,6 +1,13 @@
+use alloc::boxed::Box;
+use alloc::rc::Rc;
 use crate::st::algorithm::StaticAlgorithm;
+use core::fmt::{Debug, Formatter};
+use core::future::Future;
 use core::marker::PhantomData;
+use core::pin::Pin;
+use core::task::{Context, Poll};
 use crate::dy::IdNum;
+use crate::dy::idle::IdleSlot;
 
 
 pub struct StaticHandle{
@@ -27,6 +34,24 @@ impl StaticHandle{
         if !self.is_valid() {return false;}
         self.alg.resume(id.to_usize())
     }
+    /// Resume task with given id and pass value to it, which is returned from
+    /// [`suspend_self`](#method.suspend_self) future awaited by this task. Returns false if task
+    /// wasn't suspended or handle is invalid.
+    pub fn resume_with<V: 'static>(&self, id: IdNum, value: V) -> bool {
+        if !self.is_valid() {return false;}
+        self.alg.resume_with(id.to_usize(), Box::new(value))
+    }
+    /// Suspend current task until it's resumed. Resolves to value passed to
+    /// [`resume_with`](#method.resume_with) or `None` if task was resumed without value. Resolves
+    /// immediately to `None` if awaited outside of task or handle is invalid.
+    pub fn suspend_self<V: 'static>(&self) -> SuspendSelf<V> {
+        SuspendSelf { alg: self.alg, generation_id: self.generation_id, key: None, _phantom: PhantomData }
+    }
+    /// Wait until all other tasks are idle. Resolves at the end of beat in which no task was polled,
+    /// or immediately if handle is invalid.
+    pub fn wait_idle(&self) -> WaitIdle {
+        WaitIdle { alg: self.alg, generation_id: self.generation_id, slot: None }
+    }
     pub fn restart(&self, id: IdNum) -> bool {
         if !self.is_valid() {return false;}
         self.alg.restart(id.to_usize())
@@ -51,6 +76,21 @@ impl StaticHandle{
         if !self.is_valid() {return 0;}
         self.alg.get_registered_count()
     }
+    /// Returns value of type `C` provided by [`StaticWheel::provide`](struct.StaticWheel.html#method.provide),
+    /// or `None` if there is no such value or handle is invalid.
+    ///
+    /// Value is shared instead of borrowed because it can be replaced by next call to `provide`
+    /// and handle can outlive its wheel. To avoid cloning `Rc`, use
+    /// [`with_context`](#method.with_context).
+    pub fn context<C: 'static>(&self) -> Option<Rc<C>> {
+        if !self.is_valid() {return None;}
+        self.alg.context()
+    }
+    /// Call given function with value of type `C` provided to wheel and return its result, or
+    /// `None` if there is no such value or handle is invalid.
+    pub fn with_context<C: 'static, F, R>(&self, func: F) -> Option<R> where F: FnOnce(&C) -> R {
+        self.context().map(|value| func(&value))
+    }
     pub fn get_id_by_index(&self,index: usize)->IdNum{
         if !self.is_valid() { panic!("Handle is invalid.") }
         if index >= self.registered_count() {
@@ -58,4 +98,70 @@ impl StaticHandle{
         }
         IdNum::from_usize(index)
     }
-}
\ No newline at end of file
+}
+
+/// Future returned by [`StaticHandle::suspend_self`](struct.StaticHandle.html#method.suspend_self).
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct SuspendSelf<V> {
+    alg: &'static StaticAlgorithm,
+    generation_id: usize,
+    key: Option<usize>,
+    _phantom: PhantomData<fn() -> V>,
+}
+
+impl<V: 'static> Future for SuspendSelf<V> {
+    type Output = Option<V>;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        if this.alg.get_generation() != this.generation_id {
+            return Poll::Ready(None);
+        }
+        match this.key {
+            Some(key) => Poll::Ready(this.alg.take_resume_value(key).and_then(|v| v.downcast().ok()).map(|v| *v)),
+            None => {
+                let key = match this.alg.get_current() {
+                    Some(key) => key,
+                    None => return Poll::Ready(None),
+                };
+                drop(this.alg.take_resume_value(key));
+                if !this.alg.suspend(key) {
+                    return Poll::Ready(None);
+                }
+                this.key = Some(key);
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<V> Debug for SuspendSelf<V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SuspendSelf").field("suspended", &self.key.is_some()).finish()
+    }
+}
+
+/// Future returned by [`StaticHandle::wait_idle`](struct.StaticHandle.html#method.wait_idle).
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct WaitIdle {
+    alg: &'static StaticAlgorithm,
+    generation_id: usize,
+    slot: Option<IdleSlot>,
+}
+
+impl Future for WaitIdle {
+    type Output = ();
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        if this.alg.get_generation() != this.generation_id {
+            return Poll::Ready(());
+        }
+        this.alg.poll_idle(&mut this.slot, cx)
+    }
+}
+
+impl Debug for WaitIdle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("WaitIdle").finish()
+    }
+}
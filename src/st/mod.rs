@@ -7,7 +7,7 @@ mod config;
 mod handle;
 mod atomic_marker;
 
-pub use handle::StaticHandle;
+pub use handle::{StaticHandle, SuspendSelf, WaitIdle};
 pub use wheel::{StaticWheelDef, StaticWheel};
 pub use stt_future::StaticFuture;
 pub use config::{handle_task, FnPtrWrapper};
@@ -17,16 +17,41 @@ pub use config::{handle_task, FnPtrWrapper};
 pub(crate) enum StopReason{ None,Suspended,Cancelled,Finished,Restart,RestartSuspended /*valid if smn does restart,suspend*/ }
 
 
+/// Decides if static task is automatically restarted when it finishes or is cancelled.
+///
+/// Restarted task is polled from the beginning in the same way as after
+/// [`StaticHandle::restart`](struct.StaticHandle.html#method.restart).
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum RestartPolicy {
+    /// Task is never restarted automatically (default).
+    Never,
+    /// Task is restarted each time it finishes or is cancelled.
+    Always,
+    /// Task is restarted only when it's cancelled, when it finishes it stays inactive.
+    OnCancel,
+    /// Task is restarted when it finishes or is cancelled, but only given number of times. Counter
+    /// starts from zero each time [`StaticWheelDef`](struct.StaticWheelDef.html) is locked.
+    /// Restarts done by [`StaticHandle::restart`](struct.StaticHandle.html#method.restart) or by
+    /// restart group are not counted.
+    Times(usize),
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self { RestartPolicy::Never }
+}
+
 #[derive(Clone, Eq, PartialEq, Hash)]
 pub struct StaticParams{
     pub(crate) suspended: bool,
     pub(crate) name: Option<&'static str>,
+    pub(crate) restart_policy: RestartPolicy,
+    pub(crate) restart_group: Option<u32>,
 }
 
 
 impl StaticParams {
 
-    pub const fn new()->Self{ Self{ name: None, suspended: false} }
+    pub const fn new()->Self{ Self{ name: None, suspended: false, restart_policy: RestartPolicy::Never, restart_group: None } }
     /// Set suspended property.
     pub const fn suspend(self, value: bool) -> Self {
         Self{ suspended: value, ..self }
@@ -35,6 +60,17 @@ impl StaticParams {
     pub const fn name(mut self, name: &'static str) -> Self {
         Self{ name: Some(name), ..self }
     }
+    /// Set restart policy property, which decides if task is restarted automatically when it
+    /// finishes or is cancelled. Default is `RestartPolicy::Never`.
+    pub const fn restart_policy(self, policy: RestartPolicy) -> Self {
+        Self{ restart_policy: policy, ..self }
+    }
+    /// Set restart group property. When task is restarted automatically because of its
+    /// [`RestartPolicy`](enum.RestartPolicy.html), all other tasks with the same group are restarted
+    /// too.
+    pub const fn restart_group(self, group: u32) -> Self {
+        Self{ restart_group: Some(group), ..self }
+    }
     /// Create default parameters (suspended property set to false) with name property set to
     /// static string slice.
     pub const fn named(name: &'static str) -> Self { Self::new().name(name) }
@@ -1,3 +1,5 @@
+use alloc::boxed::Box;
+use core::any::Any;
 use core::cell::{Cell, UnsafeCell};
 use core::task::{Context, Poll, Waker};
 use crate::utils::{DropGuard, AtomicWakerRegistry, DynamicWake, to_static_waker, noop_waker};
@@ -5,7 +7,7 @@ use core::sync::atomic::{AtomicBool, Ordering};
 use crate::st::handle::StaticHandle;
 use core::mem::ManuallyDrop;
 use crate::st::config::{CANCEL_TASK, RESTART_TASK, FnPtrWrapper, UNINIT_TASK};
-use crate::st::{StopReason, StaticParams};
+use crate::st::{StopReason, StaticParams, RestartPolicy};
 use once_cell::unsync::OnceCell;
 
 /// Implementations specific for static_config! macro. Do not use directly.
@@ -17,6 +19,10 @@ pub struct StaticFuture{
     stop_reason: Cell<StopReason>,
     polling: Cell<bool>,
     start_suspended: bool,
+    restart_policy: RestartPolicy,
+    restart_group: Option<u32>,
+    restarts: Cell<usize>, //number of automatic restarts since initialization
+    resume_value: Cell<Option<Box<dyn Any>>>,
 }
 
 //this is fake for StaticFuture alone, but allows to hold it in statics
@@ -35,6 +41,10 @@ impl StaticFuture{
             stop_reason: Cell::new(StopReason::None),
             polling: Cell::new(false),
             start_suspended: params.suspended,
+            restart_policy: params.restart_policy,
+            restart_group: params.restart_group,
+            restarts: Cell::new(0),
+            resume_value: Cell::new(None),
         }
     }
     pub(crate)fn init(&self,global: &'static AtomicWakerRegistry){
@@ -42,6 +52,9 @@ impl StaticFuture{
     }
     pub(crate)fn reset(&self, defaults: bool)->bool{//true if initialized suspended
         self.set_stop_reason(if self.start_suspended && defaults {StopReason::Suspended} else {StopReason::None});
+        if defaults {
+            self.restarts.set(0);
+        }
         self.get_flags().set_runnable_relaxed(true);
         self.start_suspended
     }
@@ -50,6 +63,23 @@ impl StaticFuture{
     }
 
     pub(crate)fn get_name(&self) -> Option<&'static str> { self.name }
+    pub(crate)fn get_restart_group(&self) -> Option<u32> { self.restart_group }
+    pub(crate)fn replace_resume_value(&self, value: Option<Box<dyn Any>>) -> Option<Box<dyn Any>> {
+        self.resume_value.replace(value)
+    }
+    //true if task should be restarted after it finished or was cancelled, counts restart
+    pub(crate)fn auto_restart(&self, cancelled: bool) -> bool {
+        let restart = match self.restart_policy {
+            RestartPolicy::Never => false,
+            RestartPolicy::Always => true,
+            RestartPolicy::OnCancel => cancelled,
+            RestartPolicy::Times(max) => self.restarts.get() < max,
+        };
+        if restart {
+            self.restarts.set(self.restarts.get().saturating_add(1));
+        }
+        restart
+    }
     pub(crate)fn get_stop_reason(&self) -> StopReason { self.stop_reason.get() }
     pub(crate)fn set_stop_reason(&self, val: StopReason) { self.stop_reason.set(val); }
     pub(crate)fn is_runnable(&self) -> bool { self.get_flags().is_runnable() }
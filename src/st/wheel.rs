@@ -1,3 +1,4 @@
+use alloc::rc::Rc;
 use core::future::Future;
 use crate::dy::SuspendError;
 use core::marker::PhantomData;
@@ -59,6 +60,11 @@ impl StaticWheel{
         StaticHandle{alg,_phantom: PhantomData,generation_id: alg.get_generation()}
     }
 
+    /// Provide value to all tasks of this wheel, accessible by
+    /// [`StaticHandle::context`](struct.StaticHandle.html#method.context). Returns previous value
+    /// of the same type. Values are dropped when this wheel is dropped.
+    pub fn provide<C: 'static>(&self, value: C) -> Option<Rc<C>> { self.alg.algorithm.provide(value) }
+
     pub fn spin_block(self)->Result<(),SuspendError>{ spin_block_on(self) }
     pub fn spin_block_forever(self)->!{
         self.spin_block().unwrap();
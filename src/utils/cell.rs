@@ -1,7 +1,7 @@
 use core::cell::UnsafeCell;
 use core::fmt::{Debug, Formatter};
 use core::mem::{forget, ManuallyDrop};
-use core::ops::DerefMut;
+use core::ops::{Deref, DerefMut};
 use core::sync::atomic::*;
 use core::convert::identity;
 use core::hint::spin_loop;
@@ -127,6 +127,42 @@ impl<T> AtomicCell<T> {
         }
     }
 
+    /// Tries to clone value inside cell.
+    ///
+    /// When successful returns `Ok` with copy of value. In case of failure returns `Err`, this
+    /// happens when some other thread is now modifying this value.
+    pub fn try_clone_inner(&self) -> Result<T, ()> where T: Clone {
+        let res = self.mark.compare_exchange_weak(false, true, Ordering::AcqRel, Ordering::Acquire);
+        if res.unwrap_or_else(identity) {
+            Err(()) //other thread interfered
+        } else {
+            struct UnwindGuard<'a>(&'a AtomicBool);
+            impl<'a> Drop for UnwindGuard<'a> {
+                fn drop(&mut self) { //release lock also if clone panics
+                    self.0.store(false, Ordering::Release);
+                }
+            }
+            let guard = UnwindGuard(&self.mark);
+            //SAFETY: no other thread can write to this location while mark is set
+            let value = unsafe { (&*self.cell.get()).deref().clone() };
+            drop(guard);//explicit drop
+            Ok(value)
+        }
+    }
+
+    /// Clone value inside cell.
+    ///
+    /// AtomicCell does not use atomic load/store/cas on contained data so that it can hold structs
+    /// of arbitrary size. This method tries to clone value in busy loop until success.
+    pub fn clone_inner(&self) -> T where T: Clone {
+        loop {
+            match self.try_clone_inner() {
+                Ok(value) => return value,
+                Err(()) => spin_loop(),
+            }
+        }
+    }
+
     /// Get mutable reference to content of this struct. This method statically ensures that mutation
     /// is allowed because it takes self by mutable reference.
     #[inline(always)]
@@ -149,21 +149,6 @@ impl<I: ChunkSlabKey, T> ChunkSlab<I, T> {
         })
     }
 
-    pub fn retain(&mut self,mut func: impl FnMut(I,&T)->bool){
-        for (ci,chunk) in self.entries.iter_mut().enumerate() {
-            let offset = ci*CHUNK_SIZE;
-            for (i,val) in chunk.data.iter_mut().enumerate() {
-                if let Entry::Full(value) = val {
-                    let key = I::from_index(offset + i);
-                    if !func(key,value) { //remove
-                        *val = Entry::Empty(self.next);
-                        self.len -= 1;
-                        self.next = key;
-                    }
-                }
-            }
-        }
-    }
 }
 
 pub(crate) trait ChunkSlabKey: Copy {
@@ -193,13 +178,9 @@ impl_key!(u8,u16,u32,usize);
 mod tests {
     extern crate std;
     use alloc::string::String;
-    use alloc::collections::BTreeSet;
     use alloc::vec::Vec;
     use core::fmt::*;
-    use core::iter::successors;
     use super::*;
-    use rand::prelude::StdRng;
-    use rand::{SeedableRng, Rng};
 
     fn under_test<I: ChunkSlabKey>(max_key: I) -> ChunkSlab<I, i32> {
         let mut slab = ChunkSlab::new();
@@ -289,34 +270,6 @@ mod tests {
         assert_eq!(slab.get(k5), None);
     }
 
-    #[test]
-    fn test_retain(){
-        let rand = &mut StdRng::seed_from_u64(96024);
-        for len in successors(Some(2),|a|Some(a+rand.gen_range(5,10))).take(100).collect::<Vec<_>>() {
-            let mut slab = ChunkSlab::<u16, _>::new();
-            let mut entries = (0..len).map(|i|(i,slab.insert(i))).collect::<Vec<_>>();
-            assert_eq!(entries.len(),len);
-            assert_eq!(slab.len(),entries.len());
-            let remove = entries.iter().copied().filter(|_|rand.gen_bool(0.7)).collect::<Vec<_>>();
-            for e in &remove {
-                let a = slab.remove(e.1).unwrap();
-                let b = entries.remove(entries.iter().position(|v|e.1 == v.1).unwrap());
-                assert_eq!(a,b.0);
-            }
-            assert_eq!(slab.len(),entries.len());
-            slab.retain(|k,v|{
-                if rand.gen_bool(0.5) {
-                    let res = entries.remove(entries.iter().position(|v|v.1 == k).unwrap());
-                    assert_eq!(res.0,*v);
-                    false
-                } else { true }
-            });
-            assert_eq!(slab.len(),entries.len());
-            let slab = slab.iter().map(|(k,v)|(*v,k)).collect::<BTreeSet<_>>();
-            assert_eq!(slab,entries.into_iter().collect::<BTreeSet<_>>());
-        }
-    }
-
     #[test]
     fn test_pinning() { //never realloc structures, required by scheduler algorithm
         let mut slab = ChunkSlab::<u8, _>::new();
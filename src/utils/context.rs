@@ -0,0 +1,27 @@
+use alloc::collections::BTreeMap;
+use alloc::rc::Rc;
+use core::any::{Any, TypeId};
+use core::mem;
+use crate::utils::Ucw;
+
+/// Type-indexed values provided to all tasks of a wheel, at most one value of each type.
+pub(crate) struct ContextMap {
+    values: Ucw<BTreeMap<TypeId, Rc<dyn Any>>>,
+}
+
+impl ContextMap {
+    pub const fn new() -> Self { Self { values: Ucw::new(BTreeMap::new()) } }
+    // returns previous value of the same type, so that it's dropped outside of borrow
+    pub fn provide<T: 'static>(&self, value: T) -> Option<Rc<T>> {
+        let old = self.values.borrow_mut().insert(TypeId::of::<T>(), Rc::new(value))?;
+        Some(old.downcast().ok().expect("Internal Error: context value has wrong type."))
+    }
+    pub fn get<T: 'static>(&self) -> Option<Rc<T>> {
+        let value = self.values.borrow().get(&TypeId::of::<T>())?.clone();
+        value.downcast().ok()
+    }
+    pub fn clear(&self) {
+        let values = mem::replace(&mut *self.values.borrow_mut(), BTreeMap::new());
+        drop(values); //destructors can access this map again
+    }
+}
@@ -22,6 +22,8 @@ mod timing;
 mod chunk_slab;
 mod signal;
 mod ucw;
+mod context;
+mod queue;
 
 pub use cell::AtomicCell;
 pub use load::LoadBalance;
@@ -31,6 +33,8 @@ pub use timing::StdTimerClock;
 
 pub(crate) use chunk_slab::ChunkSlab;
 pub(crate) use ucw::Ucw;
+pub(crate) use context::ContextMap;
+pub(crate) use queue::AtomicQueue;
 
 
 /// Implement this trait if you want to create custom waker with [`to_waker`](fn.to_waker.html) function.
@@ -0,0 +1,87 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use core::ptr::null_mut;
+use core::sync::atomic::{AtomicPtr, Ordering};
+
+/// Lock-free multi-producer single-consumer queue. Producers push single items, consumer takes all
+/// of them at once, which avoids ABA problem of lock-free stacks. Queue can be closed, then items
+/// are given back to producers, so that no item is lost after consumer stopped taking them.
+pub(crate) struct AtomicQueue<T> {
+    head: AtomicPtr<Node<T>>, //most recently pushed item, or CLOSED marker
+    _phantom: PhantomData<Box<Node<T>>>,
+}
+
+struct Node<T> {
+    value: T,
+    next: *mut Node<T>,
+}
+
+// address of static is never address of allocated node
+static CLOSED: u8 = 0;
+
+unsafe impl<T: Send> Send for AtomicQueue<T> {}
+unsafe impl<T: Send> Sync for AtomicQueue<T> {}
+
+impl<T> AtomicQueue<T> {
+    pub const fn new() -> Self { Self { head: AtomicPtr::new(null_mut()), _phantom: PhantomData } }
+    fn closed_marker() -> *mut Node<T> { &CLOSED as *const u8 as *mut Node<T> }
+    // returns given item back if queue is closed
+    pub fn push(&self, value: T) -> Result<(), T> {
+        let mut head = self.head.load(Ordering::Relaxed);
+        if head == Self::closed_marker() {
+            return Err(value);
+        }
+        let node = Box::into_raw(Box::new(Node { value, next: null_mut() }));
+        loop {
+            //SAFETY: node is not shared until it's successfully pushed
+            unsafe { (*node).next = head; }
+            match self.head.compare_exchange_weak(head, node, Ordering::Release, Ordering::Relaxed) {
+                Ok(_) => return Ok(()),
+                Err(current) if current == Self::closed_marker() => {
+                    //SAFETY: node wasn't pushed, so this is still its only owner
+                    return Err(unsafe { Box::from_raw(node) }.value);
+                }
+                Err(current) => head = current,
+            }
+        }
+    }
+    // returns all items in order of pushing
+    pub fn take_all(&self) -> Vec<T> {
+        let mut head = self.head.load(Ordering::Relaxed);
+        loop {
+            if head.is_null() || head == Self::closed_marker() {
+                return Vec::new();
+            }
+            match self.head.compare_exchange_weak(head, null_mut(), Ordering::Acquire, Ordering::Relaxed) {
+                Ok(_) => return Self::collect(head),
+                Err(current) => head = current,
+            }
+        }
+    }
+    // close queue so that all following pushes fail, returns items that were pushed before
+    pub fn close(&self) -> Vec<T> {
+        let head = self.head.swap(Self::closed_marker(), Ordering::Acquire);
+        if head == Self::closed_marker() { Vec::new() } else { Self::collect(head) }
+    }
+    pub fn is_empty(&self) -> bool {
+        let head = self.head.load(Ordering::Acquire);
+        head.is_null() || head == Self::closed_marker()
+    }
+    pub fn is_closed(&self) -> bool { self.head.load(Ordering::Acquire) == Self::closed_marker() }
+    fn collect(mut ptr: *mut Node<T>) -> Vec<T> {
+        let mut items = Vec::new();
+        while !ptr.is_null() {
+            //SAFETY: whole list was detached from queue, so this is the only owner of its nodes
+            let node = unsafe { Box::from_raw(ptr) };
+            ptr = node.next;
+            items.push(node.value);
+        }
+        items.reverse();
+        items
+    }
+}
+
+impl<T> Drop for AtomicQueue<T> {
+    fn drop(&mut self) { drop(self.close()) }
+}
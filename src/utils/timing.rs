@@ -144,6 +144,16 @@ impl<C: TimerCount> TimingGroup<C> {
     pub fn count(&self) -> usize { self.info.len() }
     /// Returns time slot count for given key or `None` if this key is invalid.
     pub fn get_slot_count(&self, key: usize) -> Option<u16> { self.info.get(key).map(|v| v.proportion.get()) }
+    /// Change time slot count of entry with specific key. Time already used by this entry is kept.
+    ///
+    /// # Panics
+    /// Panics if provided key has no associated entry or time slot count argument is zero.
+    pub fn set_slot_count(&mut self, key: usize, slot_count: u16) {
+        let proportion = NonZeroU16::new(slot_count).expect("Time slot count is zero.");
+        let this = self.info.get_mut(key).expect("Error: unknown key passed to TimingGroup::set_slot_count");
+        this.proportion = proportion;
+        self.max = self.info.iter().map(|(_, v)| Self::get_proportional(v)).max().unwrap();
+    }
     /// Remove all entries from this group and reset its state.
     pub fn clear(&mut self) {
         self.info.clear();
@@ -1,6 +1,7 @@
 use core::future::Future;
 use core::pin::Pin;
 use core::task::{Context, Poll};
+use crate::budget::poll_budget;
 
 /// Helper struct for dealing with task switching.
 #[derive(Clone, Debug, Hash, Eq, PartialEq)]
@@ -25,7 +26,8 @@ impl Yield {
 
     /// When awaited it won't cause task switch.
     ///
-    /// Future returned by this method when polled always return `Poll::Ready(())`.
+    /// Future returned by this method when polled returns `Poll::Ready(())`, unless cooperative
+    /// budget of current task is exhausted (see [`consume_budget`](fn.consume_budget.html)).
     pub fn none() -> Self { Self(true) }
 
     /// When awaited yields this task specific number of times.
@@ -60,7 +62,7 @@ impl Yield {
 impl Future for Yield {
     type Output = ();
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        if self.0 { Poll::Ready(()) } else {
+        if self.0 { poll_budget(cx) } else {
             self.get_mut().0 = true;
             cx.waker().wake_by_ref();
             Poll::Pending
@@ -73,7 +75,7 @@ impl<F: FnMut() -> bool> Future for YieldWhile<F> {
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         //SAFETY: F does not care about being pinned
         let func = unsafe{ &mut self.get_unchecked_mut().0 };
-        if !func() { Poll::Ready(()) } else {
+        if !func() { poll_budget(cx) } else {
             cx.waker().wake_by_ref();
             Poll::Pending
         }
@@ -83,7 +85,7 @@ impl<F: FnMut() -> bool> Future for YieldWhile<F> {
 impl Future for YieldTimes {
     type Output = ();
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        if self.remaining == 0 { Poll::Ready(()) } else {
+        if self.remaining == 0 { poll_budget(cx) } else {
             self.as_mut().remaining -= 1;
             cx.waker().wake_by_ref();
             Poll::Pending
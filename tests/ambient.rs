@@ -0,0 +1,57 @@
+use juggle::dy::*;
+use juggle::ambient::{self, current_task, NoWheelError};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+// library code that doesn't know about wheel handle
+async fn fan_out(log: Rc<RefCell<Vec<String>>>, count: usize) {
+    let parent = current_task().unwrap();
+    for i in 0..count {
+        let log = log.clone();
+        ambient::spawn_with(SpawnParams::dyn_named(format!("child {}", i)), async move {
+            let me = current_task().unwrap();
+            log.borrow_mut().push(format!("child {} {}", i, me != parent));
+        }).unwrap();
+    }
+}
+
+#[test]
+fn test_ambient_spawn() {
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let wheel = Wheel::new();
+    let handle = wheel.handle().clone();
+    let (h, l) = (handle.clone(), log.clone());
+    handle.spawn_default(async move {
+        assert_eq!(current_task(), Ok(h.current().unwrap()));
+        fan_out(l, 2).await;
+        assert!(h.get_by_name("child 1").is_some());
+    }).unwrap();
+    assert_eq!(current_task(), Err(NoWheelError));
+    smol::block_on(wheel).unwrap();
+    assert_eq!(*log.borrow(), vec!["child 0 true", "child 1 true"]);
+    assert_eq!(ambient::spawn(async {}), Err(NoWheelError));
+}
+
+#[test]
+fn test_ambient_nested_and_run_until() {
+    let wheel = Wheel::new();
+    let handle = wheel.handle().clone();
+    let spawned = smol::block_on(wheel.run_until(async {
+        assert_eq!(current_task(), Err(NoWheelError)); //inside wheel but not inside task
+        ambient::spawn(async {}).unwrap()
+    }));
+    assert_eq!(handle.get_state(spawned), Some(State::Runnable));
+    let h = handle.clone();
+    handle.spawn_default(async move {
+        let outer = current_task().unwrap();
+        h.scope(|s| async move {
+            let inner = s.clone();
+            s.spawn_default(async move {
+                //nested wheel is current one while its tasks are polled
+                assert_eq!(current_task().ok(), inner.current());
+            }).unwrap();
+        }).await.unwrap();
+        assert_eq!(current_task(), Ok(outer));
+    }).unwrap();
+    smol::block_on(wheel).unwrap();
+}
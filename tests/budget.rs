@@ -0,0 +1,53 @@
+use juggle::dy::*;
+use juggle::*;
+use std::cell::Cell;
+use std::rc::Rc;
+
+#[test]
+fn test_budget_interleaves_busy_task() {
+    let wheel = Wheel::new();
+    let other_polled = Rc::new(Cell::new(0));
+    let flag = other_polled.clone();
+    wheel.handle().spawn_default(async move {
+        assert_eq!(remaining_budget(), Some(POLL_BUDGET));
+        let mut iterations = 0;
+        while flag.get() < 3 {
+            Yield::none().await; //never yields on its own
+            iterations += 1;
+            assert!(iterations <= 3 * POLL_BUDGET + 3, "budget wasn't enforced");
+        }
+        assert!(iterations >= 2 * POLL_BUDGET);
+    }).unwrap();
+    let counter = other_polled.clone();
+    wheel.handle().spawn_default(async move {
+        for _ in 0..3 {
+            counter.set(counter.get() + 1);
+            yield_once!();
+        }
+    }).unwrap();
+    smol::block_on(wheel).unwrap();
+    assert_eq!(other_polled.get(), 3);
+}
+
+#[test]
+fn test_consume_budget() {
+    assert_eq!(remaining_budget(), None);
+    smol::block_on(async {
+        for _ in 0..1000 { consume_budget().await; } //unlimited outside of wheel
+    });
+    let wheel = Wheel::new();
+    let yields = Rc::new(Cell::new(0));
+    let y = yields.clone();
+    wheel.handle().spawn_default(async move {
+        let mut last = remaining_budget().unwrap();
+        for _ in 0..(POLL_BUDGET * 2 + 1) {
+            consume_budget().await;
+            let now = remaining_budget().unwrap();
+            if now > last { y.set(y.get() + 1); } //budget was renewed after yield
+            last = now;
+        }
+    }).unwrap();
+    smol::block_on(wheel).unwrap();
+    assert_eq!(yields.get(), 2);
+    assert_eq!(remaining_budget(), None);
+}
@@ -0,0 +1,131 @@
+use juggle::dy::*;
+use juggle::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+struct PushOnDrop(Rc<RefCell<Vec<&'static str>>>);
+impl Drop for PushOnDrop {
+    fn drop(&mut self) { self.0.borrow_mut().push("dropped") }
+}
+
+#[test]
+fn test_cleanup_after_cancel() {
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let cleanup = Arc::new(Mutex::new(Vec::new()));
+    let wheel = Wheel::new();
+    let handle = wheel.handle().clone();
+    let c = cleanup.clone();
+    let params = SpawnParams::named("worker").on_cancel(move || {
+        let c = c.clone();
+        async move {
+            c.lock().unwrap().push("offline");
+            yield_once!();
+            c.lock().unwrap().push("closed");
+        }
+    });
+    assert!(params.has_on_cancel());
+    let l = log.clone();
+    let worker = handle.spawn(params, async move {
+        let _guard = PushOnDrop(l);
+        loop { yield_once!(); }
+    }).unwrap();
+    let (h, l, c) = (handle.clone(), log.clone(), cleanup.clone());
+    handle.spawn_default(async move {
+        yield_once!();
+        assert!(h.cancel(worker));
+        assert_eq!(h.get_state(worker), Some(State::Cancelled));
+        yield_once!();
+        //main future was dropped before cleanup started
+        assert_eq!(*l.borrow(), vec!["dropped"]);
+        assert_eq!(h.get_state(worker), Some(State::Cancelling));
+        assert_eq!(h.get_by_name("worker"), Some(worker)); //same id
+        let debug = format!("{:?}", h);
+        assert!(debug.contains("cancelling: [0x0:worker]"), "{}", debug);
+        assert!(!h.cancel(worker));
+        assert!(!h.suspend(worker));
+        while h.get_state(worker) != Some(State::Inactive) {
+            yield_once!();
+        }
+        assert_eq!(*c.lock().unwrap(), vec!["offline", "closed"]);
+    }).unwrap();
+    smol::block_on(wheel).unwrap();
+}
+
+#[test]
+fn test_cleanup_limit() {
+    let polls = Arc::new(Mutex::new(0));
+    let wheel = Wheel::new();
+    let handle = wheel.handle().clone();
+    let p = polls.clone();
+    let params = SpawnParams::default().epilogue_poll_limit(3).on_cancel(move || {
+        let p = p.clone();
+        async move {
+            loop {
+                *p.lock().unwrap() += 1;
+                yield_once!();
+            }
+        }
+    });
+    assert_eq!(params.get_epilogue_poll_limit(), 3);
+    let id = handle.spawn(params, async { loop { yield_once!(); } }).unwrap();
+    let h = handle.clone();
+    handle.spawn_default(async move {
+        h.cancel(id);
+        while h.get_state(id) != Some(State::Inactive) {
+            yield_once!();
+        }
+    }).unwrap();
+    smol::block_on(wheel).unwrap();
+    assert_eq!(*polls.lock().unwrap(), 3);
+}
+
+#[test]
+fn test_no_cleanup_when_finished_or_stopped() {
+    let runs = Arc::new(Mutex::new(0));
+    let r = runs.clone();
+    let params = SpawnParams::default().on_cancel(move || {
+        *r.lock().unwrap() += 1;
+        async {}
+    });
+    let wheel: Wheel<u32> = Wheel::new_typed();
+    let handle = wheel.handle().clone();
+    handle.spawn(params.clone(), async {}).unwrap();
+    handle.spawn(params.clone(), async { loop { yield_once!(); } }).unwrap();
+    let h = handle.clone();
+    handle.spawn_default(async move {
+        Yield::times(2).await;
+        h.exit(5);
+    }).unwrap();
+    assert_eq!(smol::block_on(wheel), Ok(5));
+    assert_eq!(*runs.lock().unwrap(), 0);
+}
+
+#[test]
+fn test_local_cleanup() {
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let wheel = Wheel::new();
+    let handle = wheel.handle().clone();
+    let worker = handle.spawn_detachable(SpawnParams::suspended(true), async {}).unwrap();
+    let (h, l) = (handle.clone(), log.clone());
+    assert!(handle.on_cancel(worker, async move {
+        l.borrow_mut().push("first");
+        drop(h);
+    }));
+    let (h, l) = (handle.clone(), log.clone());
+    assert!(handle.on_cancel(worker, async move { //replaces previous cleanup
+        yield_once!();
+        assert_eq!(h.get_state(worker), Some(State::Cancelling));
+        l.borrow_mut().push("second");
+    }));
+    assert!(handle.detach(worker).is_none()); //local cleanup can't be moved to other thread
+    let h = handle.clone();
+    handle.spawn_default(async move {
+        h.cancel(worker);
+        yield_once!();
+        assert!(!h.on_cancel(worker, async {})); //already cancelling
+    }).unwrap();
+    smol::block_on(wheel).unwrap();
+    assert_eq!(*log.borrow(), vec!["second"]);
+    assert!(!handle.on_cancel(worker, async {}));
+}
@@ -0,0 +1,269 @@
+use juggle::channel::{broadcast, mpsc, oneshot, watch, RecvError, SendError, TryRecvError, TrySendError};
+use juggle::dy::*;
+use juggle::*;
+use std::cell::Cell;
+use std::rc::Rc;
+
+#[test]
+fn test_mpsc_bounded_waits_for_receiver() {
+    let wheel = Wheel::new();
+    let handle = wheel.handle().clone();
+    let (tx, mut rx) = mpsc::channel(2);
+    let sent = Rc::new(Cell::new(0));
+    let s = sent.clone();
+    handle.spawn_default(async move {
+        for i in 0..5 {
+            tx.send(i).await.unwrap();
+            s.set(s.get() + 1);
+        }
+    }).unwrap();
+    let s = sent.clone();
+    handle.spawn_default(async move {
+        Yield::times(3).await;
+        assert_eq!(s.get(), 2); //sender waits while channel is full
+        assert_eq!(rx.len(), 2);
+        let mut received = Vec::new();
+        while let Some(value) = rx.recv().await {
+            received.push(value);
+        }
+        assert_eq!(received, vec![0, 1, 2, 3, 4]);
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Closed));
+    }).unwrap();
+    smol::block_on(wheel).unwrap();
+    assert_eq!(sent.get(), 5);
+}
+
+#[test]
+fn test_mpsc_closed_when_receiver_cancelled() {
+    let wheel = Wheel::new();
+    let handle = wheel.handle().clone();
+    let (tx, mut rx) = mpsc::channel::<u32>(1);
+    let receiver = handle.spawn_default(async move {
+        let _ = rx.recv().await;
+        unreachable!("Nothing is sent to receiver.");
+    }).unwrap();
+    let h = handle.clone();
+    handle.spawn_default(async move {
+        yield_once!();
+        assert_eq!(tx.try_send(1), Ok(()));
+        assert_eq!(tx.try_send(2), Err(TrySendError::Full(2)));
+        h.cancel(receiver);
+        //receiver is removed at the beginning of next beat, waiting sender is woken
+        assert_eq!(tx.send(3).await, Err(SendError(3)));
+        assert!(tx.is_closed());
+    }).unwrap();
+    smol::block_on(wheel).unwrap();
+
+    let (tx, mut rx) = mpsc::unbounded();
+    for i in 0..100 {
+        tx.try_send(i).unwrap();
+    }
+    rx.close();
+    assert_eq!(tx.try_send(100), Err(TrySendError::Closed(100)));
+    assert_eq!(rx.try_recv(), Ok(0)); //already sent values can be received
+}
+
+#[test]
+fn test_oneshot() {
+    let wheel = Wheel::new();
+    let handle = wheel.handle().clone();
+    let (mut tx, rx) = oneshot::channel::<u32>();
+    let h = handle.clone();
+    let waiting = handle.spawn_default(async move {
+        let _ = rx.await;
+        unreachable!("Value is never sent.");
+    }).unwrap();
+    handle.spawn_default(async move {
+        yield_once!();
+        h.cancel(waiting);
+        tx.closed().await; //receiver dropped together with its task
+        assert_eq!(tx.send(1), Err(1));
+    }).unwrap();
+    smol::block_on(wheel).unwrap();
+
+    let (tx, mut rx) = oneshot::channel();
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+    tx.send("value").unwrap();
+    assert_eq!(rx.try_recv(), Ok("value"));
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Closed));
+    let (tx, rx) = oneshot::channel::<()>();
+    drop(tx);
+    assert_eq!(smol::block_on(rx), Err(RecvError));
+}
+
+#[test]
+fn test_broadcast_lagged_receiver() {
+    let (tx, mut fast) = broadcast::channel(2);
+    let mut slow = tx.subscribe();
+    for i in 0..4 {
+        assert_eq!(tx.send(i), Ok(2));
+        assert_eq!(fast.try_recv(), Ok(i));
+    }
+    assert_eq!(slow.len(), 2);
+    assert_eq!(slow.try_recv(), Err(broadcast::TryRecvError::Lagged(2)));
+    assert_eq!(slow.try_recv(), Ok(2));
+    let mut copy = slow.clone();
+    assert_eq!(slow.try_recv(), Ok(3));
+    assert_eq!(copy.try_recv(), Ok(3));
+    assert_eq!(copy.try_recv(), Err(broadcast::TryRecvError::Empty));
+    drop(tx);
+    assert_eq!(smol::block_on(copy.recv()), Err(broadcast::RecvError::Closed));
+    drop((fast, slow, copy));
+
+    let (tx, rx) = broadcast::channel(1);
+    drop(rx);
+    assert_eq!(tx.send(5), Err(SendError(5)));
+}
+
+#[test]
+fn test_broadcast_wakes_all_receivers() {
+    let wheel = Wheel::new();
+    let handle = wheel.handle().clone();
+    let (tx, rx) = broadcast::channel::<u32>(1);
+    let total = Rc::new(Cell::new(0));
+    for mut rx in vec![rx.clone(), rx] {
+        let t = total.clone();
+        handle.spawn_default(async move {
+            while let Ok(value) = rx.recv().await {
+                t.set(t.get() + value);
+            }
+        }).unwrap();
+    }
+    let h = handle.clone();
+    handle.spawn_default(async move {
+        yield_once!();
+        for task in h.tasks() {
+            if h.current() != Some(task.id) {
+                assert_eq!(task.state, State::Waiting); //receivers don't yield busily
+            }
+        }
+        tx.send(5).unwrap();
+    }).unwrap();
+    smol::block_on(wheel).unwrap();
+    assert_eq!(total.get(), 10);
+}
+
+#[test]
+fn test_watch() {
+    let wheel = Wheel::new();
+    let handle = wheel.handle().clone();
+    let (tx, rx) = watch::channel(0);
+    let latest = Rc::new(Cell::new(Vec::new()));
+    let (mut r, l) = (rx.clone(), latest.clone());
+    let watcher = handle.spawn_default(async move {
+        let mut values = Vec::new();
+        while r.changed().await.is_ok() {
+            values.push(*r.borrow());
+            l.set(values.clone());
+        }
+    }).unwrap();
+    let h = handle.clone();
+    handle.spawn_default(async move {
+        yield_once!();
+        tx.send(1).unwrap();
+        tx.send(2).unwrap(); //watcher sees only latest value
+        yield_once!();
+        tx.send_modify(|v| *v += 1);
+        yield_once!();
+        h.cancel(watcher);
+        yield_once!();
+        assert_eq!(tx.receiver_count(), 1);
+        assert_eq!(*tx.borrow(), 3);
+        drop(rx);
+        assert_eq!(tx.send(4), Err(SendError(4)));
+        assert_eq!(tx.send_replace(5), 3);
+        let mut late = tx.subscribe();
+        assert_eq!(late.has_changed(), Ok(false));
+        tx.send(6).unwrap();
+        assert_eq!(late.has_changed(), Ok(true));
+        assert_eq!(*late.borrow_and_update(), 6);
+        drop(tx);
+        assert_eq!(late.changed().await, Err(RecvError));
+    }).unwrap();
+    smol::block_on(wheel).unwrap();
+    assert_eq!(latest.take(), vec![2, 3]);
+}
+
+#[test]
+fn test_draining_channel_consumes_budget() {
+    let wheel = Wheel::new();
+    let handle = wheel.handle().clone();
+    let (tx, mut rx) = mpsc::unbounded();
+    let count = POLL_BUDGET as usize * 4;
+    for i in 0..count {
+        tx.try_send(i).unwrap();
+    }
+    drop(tx);
+    let progress = Rc::new(Cell::new(0));
+    let p = progress.clone();
+    let other = handle.spawn_default(async move {
+        loop {
+            p.set(p.get() + 1);
+            yield_once!();
+        }
+    }).unwrap();
+    let (h, p) = (handle.clone(), progress.clone());
+    handle.spawn_default(async move {
+        let mut received = 0;
+        while rx.recv().await.is_some() {
+            received += 1;
+        }
+        assert_eq!(received, count);
+        assert!(p.get() >= 4); //other task was polled while channel was drained
+        h.cancel(other);
+    }).unwrap();
+    smol::block_on(wheel).unwrap();
+}
+
+#[test]
+fn test_mpsc_wakes_one_sender_per_slot() {
+    let wheel = Wheel::new();
+    let handle = wheel.handle().clone();
+    let (tx, mut rx) = mpsc::channel(1);
+    tx.try_send(0).unwrap();
+    let mut senders = Vec::new();
+    for i in 1..4 {
+        let tx = tx.clone();
+        senders.push(handle.spawn_default(async move { tx.send(i).await.unwrap(); }).unwrap());
+    }
+    drop(tx);
+    let h = handle.clone();
+    handle.spawn_default(async move {
+        yield_once!();
+        let waiting = || senders.iter().filter(|&&id| h.get_state(id) == Some(State::Waiting)).count();
+        assert_eq!(waiting(), 3);
+        assert_eq!(rx.try_recv(), Ok(0));
+        yield_once!();
+        assert_eq!(waiting(), 2); //only one sender was woken and took free slot
+        let mut received = vec![rx.recv().await.unwrap()];
+        while let Some(value) = rx.recv().await {
+            received.push(value);
+        }
+        received.sort();
+        assert_eq!(received, vec![1, 2, 3]);
+    }).unwrap();
+    smol::block_on(wheel).unwrap();
+}
+
+#[test]
+fn test_mpsc_cancelled_sender_does_not_take_wake() {
+    let wheel = Wheel::new();
+    let handle = wheel.handle().clone();
+    let (tx, mut rx) = mpsc::channel(1);
+    tx.try_send(0).unwrap();
+    let (tx_a, tx_b) = (tx.clone(), tx);
+    let a = handle.spawn_default(async move { tx_a.send(1).await.unwrap(); }).unwrap();
+    let b = handle.spawn_default(async move { tx_b.send(2).await.unwrap(); }).unwrap();
+    let h = handle.clone();
+    handle.spawn_default(async move {
+        yield_once!();
+        assert_eq!(h.get_state(a), Some(State::Waiting));
+        assert_eq!(h.get_state(b), Some(State::Waiting));
+        h.cancel(a); //first blocked sender is dropped without sending
+        yield_once!();
+        assert_eq!(rx.try_recv(), Ok(0)); //freed slot goes to live sender
+        assert_eq!(rx.recv().await, Some(2));
+        assert_eq!(rx.recv().await, None);
+    }).unwrap();
+    smol::block_on(wheel).unwrap();
+}
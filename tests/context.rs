@@ -0,0 +1,58 @@
+use juggle::dy::*;
+use juggle::*;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+struct Bus {
+    messages: RefCell<Vec<String>>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Config {
+    name: &'static str,
+}
+
+#[test]
+fn test_provide_context() {
+    let wheel = Wheel::new();
+    assert!(wheel.provide(Bus { messages: RefCell::new(Vec::new()) }).is_none());
+    assert!(wheel.provide(Config { name: "first" }).is_none());
+    let previous = wheel.provide(Config { name: "second" }).unwrap();
+    assert_eq!(previous.name, "first");
+    let handle = wheel.handle().clone();
+    for i in 0..3 {
+        let h = handle.clone();
+        handle.spawn_default(async move {
+            let name = h.with_context(|c: &Config| c.name).unwrap();
+            h.with_context(|bus: &Bus| bus.messages.borrow_mut().push(format!("{} {}", name, i)));
+            yield_once!();
+            assert_eq!(h.context::<u32>(), None);
+        }).unwrap();
+    }
+    let bus = handle.context::<Bus>().unwrap();
+    smol::block_on(wheel).unwrap();
+    let mut messages = bus.messages.borrow().clone();
+    messages.sort();
+    assert_eq!(messages, vec!["second 0", "second 1", "second 2"]);
+    assert!(handle.context::<Bus>().is_none()); //invalid handle
+}
+
+struct SetOnDrop(Rc<Cell<bool>>);
+impl Drop for SetOnDrop {
+    fn drop(&mut self) { self.0.set(true) }
+}
+
+#[test]
+fn test_context_dropped_with_wheel() {
+    let dropped = Rc::new(Cell::new(false));
+    let wheel = Wheel::new();
+    wheel.provide(SetOnDrop(dropped.clone()));
+    let handle = wheel.handle().clone();
+    let h = handle.clone();
+    handle.spawn_default(async move {
+        //handle can be used inside closure
+        assert_eq!(h.with_context(|_: &SetOnDrop| h.registered_count()), Some(1));
+    }).unwrap();
+    smol::block_on(wheel).unwrap();
+    assert!(dropped.get());
+}
@@ -0,0 +1,98 @@
+use juggle::dy::*;
+use juggle::*;
+use juggle::ambient::current_task;
+use std::cell::Cell;
+use std::future::poll_fn;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::channel;
+use std::sync::{Arc, Mutex};
+use std::task::{Poll, Waker};
+use std::time::Duration;
+
+#[test]
+fn test_detach_only_suspended_portable() {
+    let wheel = Wheel::new();
+    let handle = wheel.handle().clone();
+    let local = handle.spawn(SpawnParams::suspended(true), async {}).unwrap();
+    let running = handle.spawn_detachable(SpawnParams::default(), async {}).unwrap();
+    let params = SpawnParams::named("Portable").suspend(true).shared_data(5u32);
+    let portable = handle.spawn_detachable(params, async {}).unwrap();
+    assert!(handle.detach(local).is_none()); //future is not Send
+    assert!(handle.detach(running).is_none()); //not suspended
+    let task = handle.detach(portable).unwrap();
+    assert_eq!(task.name(), Some("Portable"));
+    assert_eq!(handle.get_state(portable), Some(State::Inactive));
+    assert!(handle.detach(portable).is_none());
+    assert_eq!(handle.exit_status(portable), None); //detaching is not an exit
+    assert_eq!(handle.registered_count(), 2);
+
+    let other = Wheel::new();
+    let id = other.handle().attach(task).unwrap();
+    assert_eq!(other.handle().get_state(id), Some(State::Suspended));
+    assert_eq!(other.handle().get_name(id).as_deref(), Some("Portable"));
+    assert_eq!(other.handle().with_data(id, |v: &u32| *v), Some(5));
+    assert_eq!(smol::block_on(other), Err(SuspendError));
+}
+
+#[test]
+fn test_detach_inside_task() {
+    let wheel = Wheel::new();
+    let handle = wheel.handle().clone();
+    let spawned = Rc::new(Cell::new(None));
+    let (h, s) = (handle.clone(), spawned.clone());
+    handle.spawn_default(async move {
+        let id = h.spawn_detachable(SpawnParams::suspended(true), async {}).unwrap();
+        //registry entry of this task is borrowed while it's polled
+        assert!(h.detach(id).is_none());
+        assert_eq!(h.get_state(id), Some(State::Suspended));
+        s.set(Some(id));
+    }).unwrap();
+    let moved = smol::block_on(wheel.run_until(async {
+        yield_once!(); //spawning task runs
+        handle.detach(spawned.get().unwrap()) //allowed between polls
+    }));
+    assert!(moved.is_some());
+    assert_eq!(handle.registered_count(), 0);
+}
+
+#[test]
+fn test_detached_task_woken_in_other_thread() {
+    let slot: Arc<Mutex<Option<Waker>>> = Arc::new(Mutex::new(None));
+    let fired = Arc::new(AtomicBool::new(false));
+    let (done_tx, done_rx) = channel();
+    let wheel = Wheel::new();
+    let (s, f) = (slot.clone(), fired.clone());
+    let id = wheel.handle().spawn_detachable("Waiter", async move {
+        poll_fn(|cx| {
+            if f.load(Ordering::Acquire) {
+                return Poll::Ready(());
+            }
+            *s.lock().unwrap() = Some(cx.waker().clone());
+            Poll::Pending
+        }).await;
+        done_tx.send(current_task().unwrap()).unwrap();
+    }).unwrap();
+    let handle = wheel.handle().clone();
+    smol::block_on(wheel.run_until(async {
+        yield_once!();
+        assert_eq!(handle.get_state(id), Some(State::Waiting));
+        handle.suspend(id);
+    }));
+    let task = handle.detach(id).unwrap();
+    drop(wheel);
+
+    let (id_tx, id_rx) = channel();
+    let thread = WheelThread::spawn(move |handle: &WheelHandle<'static>| {
+        let id = handle.attach(task).unwrap();
+        handle.resume(id);
+        id_tx.send(id).unwrap();
+    });
+    let moved = id_rx.recv().unwrap();
+    //waker obtained in first wheel now wakes the other one
+    fired.store(true, Ordering::Release);
+    slot.lock().unwrap().take().unwrap().wake();
+    assert_eq!(done_rx.recv_timeout(Duration::from_secs(5)), Ok(moved));
+    thread.shutdown();
+    assert_eq!(thread.join(), Ok(()));
+}
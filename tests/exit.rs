@@ -0,0 +1,80 @@
+use juggle::dy::*;
+use juggle::utils::noop_waker;
+use juggle::*;
+use std::cell::Cell;
+use std::rc::Rc;
+
+#[derive(Debug, Eq, PartialEq)]
+enum Mode { Idle, Done(u32) }
+
+impl Default for Mode {
+    fn default() -> Self { Mode::Idle }
+}
+
+#[test]
+fn test_exit_cancels_tasks() {
+    let wheel: Wheel<Mode> = Wheel::new_typed();
+    let handle = wheel.handle().clone();
+    let polls = Rc::new(Cell::new(0));
+    let p = polls.clone();
+    let other = handle.spawn_default(async move {
+        loop {
+            p.set(p.get() + 1);
+            yield_once!();
+        }
+    }).unwrap();
+    let h = handle.clone();
+    handle.spawn_default(async move {
+        Yield::times(3).await;
+        assert!(h.exit(Mode::Done(7)));
+        assert!(!h.exit(Mode::Done(8))); //already exiting
+        yield_once!();
+        unreachable!("Task should be cancelled.");
+    }).unwrap();
+    let result = smol::block_on(wheel.run_until(async {
+        Yield::times(10).await; //tasks are cancelled, but value is returned only by wheel's future
+    }));
+    assert_eq!(result, ());
+    assert_eq!(handle.get_state(other), Some(State::Inactive));
+    let count = polls.get();
+    assert_eq!(smol::block_on(wheel), Ok(Mode::Done(7)));
+    assert_eq!(polls.get(), count);
+}
+
+#[test]
+fn test_exit_block_on() {
+    let wheel: Wheel<Mode> = Wheel::new_typed();
+    let handle = wheel.handle().clone();
+    handle.spawn_default(async move { loop { yield_once!(); } }).unwrap();
+    let h = handle.clone();
+    handle.spawn_default(async move {
+        Yield::times(5).await;
+        h.exit(Mode::Done(1));
+    }).unwrap();
+    assert_eq!(block_on(wheel, || {}, &noop_waker()), Ok(Mode::Done(1)));
+
+    //all tasks finished without exit
+    let wheel: Wheel<Mode> = Wheel::new_typed();
+    wheel.handle().spawn_default(Yield::times(2)).unwrap();
+    assert_eq!(wheel.spin_block(), Ok(Mode::Idle));
+
+    //exit from outside of task
+    let wheel: Wheel<u32> = Wheel::new_typed();
+    wheel.handle().spawn_default(async { loop { yield_once!(); } }).unwrap();
+    assert!(wheel.handle().exit(3));
+    assert_eq!(wheel.spin_block(), Ok(3));
+}
+
+#[test]
+fn test_exit_after_failure() {
+    let wheel: Wheel<u32, SuspendError> = Wheel::new_typed();
+    let handle = wheel.handle().clone();
+    handle.spawn_fallible(SpawnParams::default().on_error(ErrorPolicy::StopWheel), async {
+        Err(SuspendError)
+    }).unwrap();
+    let h = handle.clone();
+    handle.spawn_default(async move {
+        assert!(!h.exit(5));
+    }).unwrap();
+    assert_eq!(wheel.spin_block(), Err(SuspendError));
+}
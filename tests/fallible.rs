@@ -0,0 +1,129 @@
+use juggle::dy::*;
+use juggle::*;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum TestError {
+    Suspended,
+    Failed(u32),
+}
+
+impl From<SuspendError> for TestError {
+    fn from(_: SuspendError) -> Self { TestError::Suspended }
+}
+
+impl From<u32> for TestError {
+    fn from(v: u32) -> Self { TestError::Failed(v) }
+}
+
+async fn fail_after(yields: usize, code: u32) -> Result<(), u32> {
+    Yield::times(yields).await;
+    Err(code)
+}
+
+#[test]
+fn test_log_errors() {
+    let wheel: Wheel<(), TestError> = Wheel::new_typed();
+    let handle = wheel.handle().clone();
+    assert_eq!(SpawnParams::default().get_error_policy(), ErrorPolicy::Log);
+    let first = handle.spawn_fallible(SpawnParams::default(), fail_after(2, 1)).unwrap();
+    let second = handle.spawn_fallible(SpawnParams::default(), fail_after(1, 2)).unwrap();
+    handle.spawn_fallible::<_, _, TestError>(SpawnParams::default(), async { Ok(()) }).unwrap();
+    smol::block_on(wheel).unwrap();
+    assert_eq!(handle.take_errors(), vec![]); //handle was invalidated with wheel
+
+    let wheel: Wheel<(), TestError> = Wheel::new_typed();
+    let handle = wheel.handle().clone();
+    handle.spawn_fallible(SpawnParams::default(), fail_after(2, 1)).unwrap();
+    handle.spawn_fallible(SpawnParams::default(), fail_after(1, 2)).unwrap();
+    let errors = smol::block_on(wheel.run_until(async {
+        Yield::times(5).await;
+        handle.take_errors()
+    }));
+    assert_eq!(errors, vec![(second, TestError::Failed(2)), (first, TestError::Failed(1))]);
+    assert_eq!(wheel.handle().take_errors(), vec![]);
+}
+
+#[test]
+fn test_error_capacity() {
+    let wheel: Wheel<(), TestError> = Wheel::new_typed();
+    let handle = wheel.handle().clone();
+    for code in 0..100 {
+        handle.spawn_fallible(SpawnParams::default(), fail_after(0, code)).unwrap();
+    }
+    let errors = smol::block_on(wheel.run_until(async {
+        yield_once!();
+        handle.take_errors()
+    }));
+    assert_eq!(errors.len(), 64); //only most recent errors are kept
+    assert_eq!(errors.last().map(|(_, e)| e.clone()), Some(TestError::Failed(99)));
+    assert_eq!(handle.dropped_errors(), 36);
+
+    assert!(handle.set_error_capacity(2));
+    for code in 0..3 {
+        handle.spawn_fallible(SpawnParams::default(), fail_after(0, code)).unwrap();
+    }
+    let errors = smol::block_on(wheel.run_until(async {
+        yield_once!();
+        handle.set_error_capacity(1); //shrinking discards oldest errors
+        handle.take_errors()
+    }));
+    assert_eq!(errors.len(), 1);
+    assert_eq!(handle.dropped_errors(), 38);
+    handle.set_error_capacity(0);
+    handle.spawn_fallible(SpawnParams::default(), fail_after(0, 7)).unwrap();
+    smol::block_on(wheel).unwrap();
+    assert_eq!(handle.dropped_errors(), 0); //handle invalidated with wheel
+}
+
+#[test]
+fn test_cancel_group() {
+    let wheel: Wheel<(), TestError> = Wheel::new_typed();
+    let handle = wheel.handle().clone();
+    let member = handle.spawn(SpawnParams::default().group(1), async { loop { yield_once!(); } }).unwrap();
+    let other = handle.spawn(SpawnParams::default().group(2), Yield::times(10)).unwrap();
+    let failing = handle.spawn_fallible(SpawnParams::default().group(1).on_error(ErrorPolicy::CancelGroup), fail_after(3, 7)).unwrap();
+    let h = handle.clone();
+    handle.spawn_default(async move {
+        Yield::times(5).await;
+        assert_eq!(h.get_state(member), Some(State::Inactive));
+        assert_eq!(h.get_state(failing), Some(State::Inactive));
+        assert_eq!(h.get_state(other), Some(State::Runnable));
+        assert_eq!(h.take_errors(), vec![(failing, TestError::Failed(7))]);
+    }).unwrap();
+    smol::block_on(wheel).unwrap();
+}
+
+#[test]
+fn test_stop_wheel() {
+    let wheel: Wheel<(), TestError> = Wheel::new_typed();
+    let handle = wheel.handle().clone();
+    handle.spawn(SpawnParams::default(), async { loop { yield_once!(); } }).unwrap();
+    handle.spawn_fallible(SpawnParams::default().on_error(ErrorPolicy::StopWheel), fail_after(3, 5)).unwrap();
+    handle.spawn_fallible(SpawnParams::default().on_error(ErrorPolicy::StopWheel), fail_after(3, 6)).unwrap();
+    let wheel = wheel.lock();
+    assert_eq!(smol::block_on(wheel), Err(TestError::Failed(5)));
+
+    //wheel can be used again after it was stopped
+    let wheel: Wheel<(), TestError> = Wheel::new_typed();
+    let handle = wheel.handle().clone();
+    handle.spawn_fallible(SpawnParams::default().on_error(ErrorPolicy::StopWheel), fail_after(1, 1)).unwrap();
+    handle.spawn(SpawnParams::default(), async { loop { yield_once!(); } }).unwrap();
+    assert!(!wheel.poll_for(1000u32, &ZeroClock)); //stopped, all tasks were cancelled
+    assert_eq!(wheel.handle().registered_count(), 0);
+    let wheel = smol::block_on(async move {
+        let mut wheel = wheel;
+        assert_eq!((&mut wheel).await, Err(TestError::Failed(1)));
+        wheel
+    });
+    assert_eq!(wheel.handle().registered_count(), 0);
+    wheel.handle().spawn(SpawnParams::suspended(true), async {}).unwrap();
+    assert_eq!(wheel.spin_block(), Err(TestError::Suspended));
+}
+
+struct ZeroClock;
+impl juggle::utils::TimerClock for ZeroClock {
+    type Duration = u32;
+    type Instant = u32;
+    fn start(&self) -> u32 { 0 }
+    fn stop(&self, _: u32) -> u32 { 0 }
+}
@@ -0,0 +1,100 @@
+use juggle::dy::*;
+use juggle::*;
+use juggle::utils::TimerClock;
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Duration;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+#[test]
+fn test_exit_reasons() {
+    let wheel = Wheel::new();
+    let handle = wheel.handle().clone();
+    let completed = handle.spawn(SpawnParams::named("completed"), async {}).unwrap();
+    let failed = handle.spawn_fallible(SpawnParams::named("failed"), async { Err::<(), _>(SuspendError) }).unwrap();
+    let cancelled = handle.spawn_default(async { loop { yield_once!(); } }).unwrap();
+    let params = SpawnParams::named("timed out").epilogue_poll_limit(2).on_cancel(|| async { loop { yield_once!(); } });
+    let timed_out = handle.spawn(params, async { loop { yield_once!(); } }).unwrap();
+    assert_eq!(handle.exit_status(completed), None);
+    let h = handle.clone();
+    handle.spawn_default(async move {
+        h.cancel(cancelled);
+        h.cancel(timed_out);
+        Yield::times(5).await;
+        let reasons: Vec<_> = h.recent_exits().into_iter().map(|s| (s.id, s.reason)).collect();
+        assert_eq!(reasons, vec![(completed, ExitReason::Completed), (failed, ExitReason::Failed),
+                                 (cancelled, ExitReason::Cancelled), (timed_out, ExitReason::TimedOut)]);
+        let status = h.exit_status(timed_out).unwrap();
+        assert_eq!(status.name.as_deref(), Some("timed out"));
+        assert!(status.exit_beat > h.exit_status(completed).unwrap().exit_beat);
+    }).unwrap();
+    smol::block_on(wheel).unwrap();
+}
+
+#[derive(Clone, Default)]
+struct ManualClock(Rc<Cell<Duration>>);
+
+impl TimerClock for ManualClock {
+    type Duration = Duration;
+    type Instant = Duration;
+    fn start(&self) -> Duration { self.0.get() }
+    fn stop(&self, start: Duration) -> Duration { self.0.get() - start }
+}
+
+#[test]
+fn test_exit_time() {
+    let wheel = Wheel::new();
+    let handle = wheel.handle().clone();
+    let early = handle.spawn_default(async {}).unwrap();
+    smol::block_on(wheel.run_until(async { yield_while!(handle.registered_count() > 0); }));
+    assert_eq!(handle.exit_status(early).unwrap().exit_time, None); //wheel had no clock
+    let clock = ManualClock::default();
+    clock.0.set(Duration::from_millis(100));
+    assert!(handle.set_history_clock(clock.clone()));
+    let first = handle.spawn_default(async {}).unwrap();
+    let c = clock.clone();
+    let second = handle.spawn_default(async move {
+        c.0.set(Duration::from_millis(130));
+        yield_once!();
+    }).unwrap();
+    smol::block_on(wheel.run_until(async { yield_while!(handle.registered_count() > 0); }));
+    let first = handle.exit_status(first).unwrap();
+    let second = handle.exit_status(second).unwrap();
+    assert_eq!(first.exit_time, Some(Duration::from_millis(0)));
+    assert_eq!(second.exit_time, Some(Duration::from_millis(30)));
+    assert!(second.exit_beat > first.exit_beat);
+}
+
+#[test]
+fn test_history_capacity() {
+    let wheel = Wheel::new();
+    let handle = wheel.handle().clone();
+    assert!(handle.set_history_capacity(3));
+    let ids: Vec<_> = (0..5).map(|i| handle.spawn_default(Yield::times(i)).unwrap()).collect();
+    let result = smol::block_on(wheel.run_until(async {
+        yield_while!(handle.registered_count() > 0);
+        handle.recent_exits().into_iter().map(|s| s.id).collect::<Vec<_>>()
+    }));
+    assert_eq!(result, &ids[2..]);
+    assert_eq!(handle.exit_status(ids[0]), None);
+    handle.set_history_capacity(1);
+    assert_eq!(handle.recent_exits().len(), 1);
+    handle.set_history_capacity(0);
+    handle.spawn_default(async {}).unwrap();
+    smol::block_on(wheel.run_until(async { yield_while!(handle.registered_count() > 0); }));
+    assert!(handle.is_valid() && handle.recent_exits().is_empty());
+}
+
+#[test]
+fn test_panicked_task() {
+    let wheel = Wheel::new();
+    let handle = wheel.handle().clone();
+    let id = handle.spawn(SpawnParams::named("faulty"), async {
+        yield_once!();
+        panic!("Task failure.");
+    }).unwrap();
+    let result = catch_unwind(AssertUnwindSafe(|| smol::block_on(wheel.run_until(core::future::pending::<()>()))));
+    assert!(result.is_err());
+    let status = handle.exit_status(id).unwrap();
+    assert_eq!((status.name.as_deref(), status.reason), (Some("faulty"), ExitReason::Panicked));
+}
@@ -0,0 +1,214 @@
+mod common;
+pub use common::*;
+use juggle::dy::*;
+use juggle::*;
+use juggle::utils::TimerClock;
+use std::cell::{Cell, RefCell};
+use std::time::Duration;
+use std::rc::Rc;
+
+fn execution_order(wheel: Wheel<'static>) -> Vec<&'static str> {
+    let signal = Signal::new();
+    let order = Rc::new(RefCell::new(Vec::new()));
+    let (sig, o) = (signal.clone(), order.clone());
+    wheel.handle().spawn_default(async move {
+        sig.await;
+        o.borrow_mut().push("a");
+    }).unwrap();
+    let o = order.clone();
+    wheel.handle().spawn_default(async move {
+        for _ in 0..3 {
+            o.borrow_mut().push("b");
+            yield_once!();
+        }
+    }).unwrap();
+    let o = order.clone();
+    wheel.handle().spawn_default(async move {
+        yield_once!();
+        signal.signal(true);
+        for _ in 0..2 {
+            o.borrow_mut().push("c");
+            yield_once!();
+        }
+    }).unwrap();
+    smol::block_on(wheel).unwrap();
+    order.take()
+}
+
+#[test]
+fn test_unordered_policy() {
+    assert_eq!(execution_order(Wheel::new()), vec!["b", "b", "c", "a", "b", "c"]);
+    assert_eq!(execution_order(Wheel::with_policy(UnorderedPolicy::new())), vec!["b", "b", "c", "a", "b", "c"]);
+}
+
+#[test]
+fn test_fifo_policy() {
+    //woken task is moved at the end of queue
+    assert_eq!(execution_order(Wheel::with_policy(FifoPolicy::new())), vec!["b", "b", "c", "b", "c", "a"]);
+}
+
+#[test]
+fn test_custom_policy() {
+    #[derive(Default)]
+    struct Reversed { tasks: Vec<IdNum>, left: Vec<IdNum>, in_beat: bool }
+    impl SchedulingPolicy for Reversed {
+        fn insert(&mut self, id: IdNum, _: &SpawnParams) { self.tasks.push(id); }
+        fn remove(&mut self, id: IdNum) {
+            self.tasks.retain(|&k| k != id);
+            self.left.retain(|&k| k != id);
+        }
+        fn next(&mut self, ctx: &PolicyContext<'_>) -> Option<IdNum> {
+            if !self.in_beat {
+                self.in_beat = true;
+                self.left = self.tasks.clone();
+            }
+            while let Some(id) = self.left.pop() {
+                if ctx.is_pollable(id) { return Some(id); }
+            }
+            self.in_beat = false;
+            None
+        }
+    }
+    assert_eq!(execution_order(Wheel::with_policy(Reversed::default())), vec!["b", "c", "b", "a", "c", "b"]);
+}
+
+#[derive(Clone, Default)]
+struct ManualClock(Rc<Cell<Duration>>);
+
+impl TimerClock for ManualClock {
+    type Duration = Duration;
+    type Instant = Duration;
+    fn start(&self) -> Duration { self.0.get() }
+    fn stop(&self, start: Duration) -> Duration { self.0.get() - start }
+}
+
+#[test]
+fn test_edf_order() {
+    let wheel = Wheel::with_policy(EdfPolicy::new(ManualClock::default()));
+    let order = Rc::new(RefCell::new(Vec::new()));
+    let ms = Duration::from_millis;
+    for &(name, deadline) in &[("30", Some(Deadline::After(ms(30)))), ("none", None),
+        ("10", Some(Deadline::At(ms(10)))), ("20", Some(Deadline::After(ms(20))))] {
+        let params = match deadline {
+            Some(d) => SpawnParams::with_deadline(d),
+            None => SpawnParams::default(),
+        };
+        assert_eq!(params.get_deadline(), deadline);
+        let o = order.clone();
+        wheel.handle().spawn(params, async move {
+            for _ in 0..2 {
+                o.borrow_mut().push(name);
+                yield_once!();
+            }
+        }).unwrap();
+    }
+    smol::block_on(wheel).unwrap();
+    assert_eq!(*order.borrow(), vec!["10", "20", "30", "none", "10", "20", "30", "none"]);
+}
+
+#[test]
+fn test_edf_missed_deadlines() {
+    let clock = ManualClock::default();
+    let wheel = Wheel::with_policy(EdfPolicy::new(clock.clone()));
+    let handle = wheel.handle().clone();
+    let ms = Duration::from_millis;
+    let other = handle.spawn(SpawnParams::with_deadline(Deadline::After(ms(5))), Yield::times(2)).unwrap();
+    let no_deadline = handle.spawn_default(Yield::times(2)).unwrap();
+    let h = handle.clone();
+    let task = handle.spawn(SpawnParams::with_deadline(Deadline::After(ms(10))), async move {
+        let this = h.current().unwrap();
+        assert_eq!(h.missed_deadlines(this), Some(0));
+        clock.0.set(ms(15));
+        yield_once!(); //polled after deadline
+        assert_eq!(h.missed_deadlines(this), Some(1));
+        assert_eq!(h.missed_deadlines(other), Some(1));
+        assert_eq!(h.missed_deadlines(no_deadline), Some(0));
+        yield_once!(); //same deadline is counted only once
+        assert_eq!(h.missed_deadlines(this), Some(1));
+        assert!(h.set_deadline(this, Deadline::After(ms(10))));
+        yield_once!();
+        assert_eq!(h.missed_deadlines(this), Some(1));
+        clock.0.set(ms(30));
+        yield_once!();
+        assert_eq!(h.missed_deadlines(this), Some(2));
+    }).unwrap();
+    smol::block_on(wheel).unwrap();
+    assert_eq!(handle.missed_deadlines(task), None);
+
+    //default policy doesn't track deadlines
+    let wheel = Wheel::new();
+    let id = wheel.handle().spawn(SpawnParams::with_deadline(Deadline::At(ms(1))), async {}).unwrap();
+    assert!(!wheel.handle().set_deadline(id, Deadline::At(ms(2))));
+    assert_eq!(wheel.handle().missed_deadlines(id), None);
+}
+
+#[test]
+fn test_edf_waiting_task_misses_deadline() {
+    let clock = ManualClock::default();
+    let wheel = Wheel::with_policy(EdfPolicy::new(clock.clone()));
+    let handle = wheel.handle().clone();
+    let ms = Duration::from_millis;
+    let signal = Signal::new();
+    let sig = signal.clone();
+    let waiting = handle.spawn(SpawnParams::with_deadline(Deadline::After(ms(10))), async move {
+        sig.await;
+    }).unwrap();
+    let h = handle.clone();
+    handle.spawn_default(async move {
+        yield_once!(); //waiting task was polled before its deadline
+        clock.0.set(ms(15));
+        assert_eq!(h.missed_deadlines(waiting), Some(1));
+        yield_once!();
+        assert_eq!(h.missed_deadlines(waiting), Some(1)); //counted once when beat started
+        assert_eq!(h.get_state(waiting), Some(State::Waiting));
+        signal.signal(true);
+    }).unwrap();
+    smol::block_on(wheel).unwrap();
+}
+
+fn spawn_counting(handle: &WheelHandle<'static>, params: SpawnParams, clock: &ManualClock, total: &Rc<Cell<usize>>) -> (IdNum, Rc<Cell<usize>>) {
+    let count = Rc::new(Cell::new(0));
+    let (clock, total, c) = (clock.clone(), total.clone(), count.clone());
+    let id = handle.spawn(params, async move {
+        while total.get() < 300 {
+            clock.0.set(clock.0.get() + Duration::from_millis(1)); //each poll takes 1ms
+            total.set(total.get() + 1);
+            c.set(c.get() + 1);
+            yield_once!();
+        }
+    }).unwrap();
+    (id, count)
+}
+
+#[test]
+fn test_fair_share_weights() {
+    let clock = ManualClock::default();
+    let total = Rc::new(Cell::new(0));
+    let wheel = Wheel::with_policy(FairSharePolicy::new(clock.clone()));
+    assert_eq!(SpawnParams::default().get_weight(), 1);
+    let (_, heavy) = spawn_counting(wheel.handle(), SpawnParams::default().weight(2), &clock, &total);
+    let (_, light) = spawn_counting(wheel.handle(), SpawnParams::default(), &clock, &total);
+    let signal = Signal::new();
+    wheel.handle().spawn_default(signal.clone()).unwrap(); //waiting task must not stall the wheel
+    signal.signal(true);
+    smol::block_on(wheel).unwrap();
+    assert!((195..=205).contains(&heavy.get()), "heavy: {}", heavy.get());
+    assert!((95..=105).contains(&light.get()), "light: {}", light.get());
+}
+
+#[test]
+fn test_fair_share_set_weight() {
+    let clock = ManualClock::default();
+    let total = Rc::new(Cell::new(0));
+    let wheel = Wheel::with_policy(FairSharePolicy::new(clock.clone()));
+    let (first, first_count) = spawn_counting(wheel.handle(), SpawnParams::default(), &clock, &total);
+    let (_, second_count) = spawn_counting(wheel.handle(), SpawnParams::default(), &clock, &total);
+    assert!(wheel.handle().set_weight(first, 3));
+    smol::block_on(wheel).unwrap();
+    assert!((220..=230).contains(&first_count.get()), "first: {}", first_count.get());
+    assert!((70..=80).contains(&second_count.get()), "second: {}", second_count.get());
+
+    let wheel = Wheel::new();
+    let id = wheel.handle().spawn_default(async {}).unwrap();
+    assert!(!wheel.handle().set_weight(id, 2));
+}
@@ -0,0 +1,106 @@
+use juggle::dy::*;
+use juggle::utils::TimerClock;
+use juggle::*;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+#[derive(Clone, Default)]
+struct StepClock(Rc<Cell<u32>>);
+
+impl TimerClock for StepClock {
+    type Duration = u32;
+    type Instant = u32;
+    fn start(&self) -> u32 { self.0.get() }
+    fn stop(&self, start: u32) -> u32 { self.0.get() - start }
+}
+
+#[test]
+fn test_poll_for_keeps_order() {
+    let clock = StepClock::default();
+    let wheel = Wheel::new();
+    let order = Rc::new(RefCell::new(Vec::new()));
+    for i in 0..3 {
+        let (clock, order) = (clock.clone(), order.clone());
+        wheel.handle().spawn_default(async move {
+            for _ in 0..3 {
+                clock.0.set(clock.0.get() + 1); //each poll takes 1 unit of time
+                order.borrow_mut().push(i);
+                yield_once!();
+            }
+        }).unwrap();
+    }
+    assert!(wheel.poll_for(2, &clock));
+    assert_eq!(*order.borrow(), vec![0, 1]);
+    //continue from task 2 instead of starting from the first task
+    assert!(wheel.poll_for(2, &clock));
+    assert_eq!(*order.borrow(), vec![0, 1, 2, 0]);
+    assert!(wheel.poll_for(4, &clock));
+    assert_eq!(*order.borrow(), vec![0, 1, 2, 0, 1, 2, 0, 1]);
+    assert!(!wheel.poll_for(100, &clock)); //all tasks finished
+    assert_eq!(*order.borrow(), vec![0, 1, 2, 0, 1, 2, 0, 1, 2]);
+    assert_eq!(wheel.handle().registered_count(), 0);
+    assert!(!wheel.poll_for(0, &clock));
+}
+
+#[test]
+fn test_poll_for_waiting() {
+    let clock = StepClock::default();
+    let wheel = Wheel::new();
+    let handle = wheel.handle().clone();
+    let waiting = handle.spawn_default(std::future::pending::<()>()).unwrap();
+    let suspended = handle.spawn(SpawnParams::suspended(true), async {}).unwrap();
+    assert!(!wheel.poll_for(10, &clock)); //returns early when nothing can be polled
+    assert_eq!(handle.get_state(waiting), Some(State::Waiting));
+    assert!(handle.resume(suspended));
+    assert!(!wheel.poll_for(10, &clock));
+    assert_eq!(handle.get_state(suspended), Some(State::Inactive));
+}
+
+#[test]
+fn test_poll_for_after_exit() {
+    let clock = StepClock::default();
+    let wheel: Wheel<u32> = Wheel::new_typed();
+    let handle = wheel.handle().clone();
+    let polls = Rc::new(Cell::new(0));
+    let p = polls.clone();
+    handle.spawn_default(async move {
+        loop {
+            p.set(p.get() + 1);
+            yield_once!();
+        }
+    }).unwrap();
+    let h = handle.clone();
+    handle.spawn_default(async move {
+        yield_once!();
+        h.exit(5);
+    }).unwrap();
+    let mut calls = 0;
+    while wheel.poll_for(1, &clock) {
+        calls += 1;
+        assert!(calls < 10, "Stopped wheel is still polled.");
+    }
+    assert_eq!(handle.registered_count(), 0); //all tasks were cancelled
+    let polled = polls.get();
+    assert!(!wheel.poll_for(1, &clock));
+    assert_eq!(polls.get(), polled);
+    assert_eq!(smol::block_on(wheel), Ok(5)); //exit value is kept until wheel is awaited
+}
+
+#[test]
+fn test_poll_for_after_stop_wheel() {
+    let clock = StepClock::default();
+    let wheel: Wheel = Wheel::new();
+    let handle = wheel.handle().clone();
+    handle.spawn_default(async { loop { yield_once!(); } }).unwrap();
+    handle.spawn_fallible(SpawnParams::default().on_error(ErrorPolicy::StopWheel), async {
+        yield_once!();
+        Err(SuspendError)
+    }).unwrap();
+    let mut calls = 0;
+    while wheel.poll_for(1, &clock) {
+        calls += 1;
+        assert!(calls < 10, "Stopped wheel is still polled.");
+    }
+    assert_eq!(handle.registered_count(), 0);
+    assert_eq!(smol::block_on(wheel), Err(SuspendError));
+}
@@ -0,0 +1,86 @@
+use juggle::dy::*;
+use juggle::*;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+/// Runs given closure when dropped.
+struct OnDrop<F: FnMut()>(F);
+impl<F: FnMut()> Drop for OnDrop<F> {
+    fn drop(&mut self) { (self.0)() }
+}
+
+#[test]
+fn test_spawn_and_cancel_from_drop() {
+    let wheel = Wheel::new();
+    let handle = wheel.handle().clone();
+    let cleaned = Rc::new(Cell::new(false));
+    let sibling = handle.spawn_default(async {
+        loop { yield_once!(); }
+    }).unwrap();
+
+    let (h, c) = (handle.clone(), cleaned.clone());
+    let guard = OnDrop(move || {
+        assert!(h.cancel(sibling));
+        let c = c.clone();
+        h.spawn_default(async move { c.set(true) }).unwrap();
+    });
+    let task = handle.spawn_default(async move {
+        let _guard = guard;
+        loop { yield_once!(); }
+    }).unwrap();
+
+    let h = handle.clone();
+    handle.spawn_default(async move {
+        Yield::times(3).await;
+        h.cancel(task);
+    }).unwrap();
+
+    smol::block_on(wheel).unwrap();
+    assert!(cleaned.get());
+    assert!(!handle.is_valid());
+}
+
+#[test]
+fn test_nested_drops_on_exit() {
+    let wheel: Wheel<u32> = Wheel::new_typed();
+    let handle = wheel.handle().clone();
+    let order = Rc::new(RefCell::new(Vec::new()));
+    for i in 0..5 {
+        let (h, o) = (handle.clone(), order.clone());
+        let guard = OnDrop(move || {
+            o.borrow_mut().push(i);
+            //cancelling and inspecting other tasks while they are being removed must be legal
+            for id in h.snapshot().unwrap().tasks.iter().map(|t| t.id) {
+                h.cancel(id);
+            }
+        });
+        handle.spawn_default(async move {
+            let _guard = guard;
+            loop { yield_once!(); }
+        }).unwrap();
+    }
+    let h = handle.clone();
+    handle.spawn_default(async move {
+        yield_once!();
+        h.exit(9);
+    }).unwrap();
+    assert_eq!(smol::block_on(wheel), Ok(9));
+    let mut order = order.borrow().clone();
+    order.sort();
+    assert_eq!(order, vec![0, 1, 2, 3, 4]);
+}
+
+#[test]
+fn test_drop_wheel_with_reentrant_task() {
+    let wheel = Wheel::new();
+    let handle = wheel.handle().clone();
+    let spawned = Rc::new(Cell::new(None));
+    let (h, s) = (handle.clone(), spawned.clone());
+    let guard = OnDrop(move || s.set(Some(h.spawn_default(async {}).is_some())));
+    handle.spawn_default(async move {
+        let _guard = guard;
+        loop { yield_once!(); }
+    }).unwrap();
+    drop(wheel); //handle is already invalid when tasks are dropped
+    assert_eq!(spawned.get(), Some(false));
+}
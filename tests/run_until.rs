@@ -0,0 +1,87 @@
+mod common;
+pub use common::*;
+use juggle::dy::*;
+use juggle::*;
+use std::cell::Cell;
+use std::time::Duration;
+
+#[test]
+fn test_run_until_reuse() {
+    let counter = Cell::new(0);
+    let wheel = Wheel::new();
+    let background = wheel.handle().spawn_default(async {
+        loop {
+            counter.set(counter.get() + 1);
+            yield_once!();
+        }
+    }).unwrap();
+    let value = smol::block_on(wheel.run_until(async {
+        yield_while!(counter.get() < 5);
+        42
+    }));
+    assert_eq!(value, 42);
+    assert_eq!(wheel.handle().get_state(background), Some(State::Runnable));
+    //reuse the same wheel
+    let value = smol::block_on(wheel.run_until(async {
+        yield_while!(counter.get() < 10);
+        "second"
+    }));
+    assert_eq!(value, "second");
+    assert!(wheel.handle().cancel(background));
+    smol::block_on(wheel).unwrap();
+}
+
+#[test]
+fn test_run_until_waiting() {
+    let wheel = Wheel::new();
+    let signal = Signal::new();
+    let task = wheel.handle().spawn_default(signal.clone()).unwrap();
+    //all tasks wait while future waits for timer
+    let value = smol::block_on(wheel.run_until(async {
+        signal_after(Duration::from_millis(50)).await;
+        1
+    }));
+    assert_eq!(value, 1);
+    assert_eq!(wheel.handle().get_state(task), Some(State::Waiting));
+    //future waits for task
+    let handle = wheel.handle().clone();
+    let value = spin_block_on(wheel.run_until(async {
+        signal.signal(true);
+        yield_while!(handle.get_state(task) != Some(State::Inactive));
+        2
+    }));
+    assert_eq!(value, 2);
+    assert_eq!(wheel.handle().registered_count(), 0);
+}
+
+#[test]
+#[should_panic(expected = "Cannot run wheel inside its own task.")]
+fn test_run_until_inside_task() {
+    let wheel = Box::leak(Box::new(Wheel::new()));
+    let w: &'static Wheel<'static> = wheel;
+    wheel.handle().spawn_default(async move {
+        w.run_until(async {}).await;
+    }).unwrap();
+    smol::block_on(wheel.run_until(Yield::times(2)));
+}
+
+#[test]
+fn test_run_until_after_exit() {
+    use juggle::channel::{oneshot, RecvError};
+    let wheel: Wheel<u32> = Wheel::new_typed();
+    let handle = wheel.handle().clone();
+    let (tx, rx) = oneshot::channel::<&str>();
+    let h = handle.clone();
+    let worker = handle.spawn_default(async move {
+        let _tx = tx;
+        yield_once!();
+        h.exit(7);
+        yield_once!();
+        unreachable!("Wheel is stopped.");
+    }).unwrap();
+    let result = smol::block_on(wheel.run_until(rx));
+    assert_eq!(result, Err(RecvError)); //sender was dropped with cancelled task
+    assert_eq!(handle.get_state(worker), Some(State::Inactive));
+    assert_eq!(handle.registered_count(), 0);
+    assert_eq!(smol::block_on(wheel), Ok(7)); //exit value is kept until wheel is awaited
+}
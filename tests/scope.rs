@@ -0,0 +1,89 @@
+use juggle::dy::*;
+use juggle::*;
+use std::cell::Cell;
+use std::rc::Rc;
+
+#[test]
+fn test_scope_waits_for_children() {
+    let wheel = Wheel::new();
+    let handle = wheel.handle().clone();
+    let outer_polls = Rc::new(Cell::new(0));
+    let o = outer_polls.clone();
+    let other = handle.spawn_default(async move {
+        loop {
+            o.set(o.get() + 1);
+            yield_once!();
+        }
+    }).unwrap();
+    let h = handle.clone();
+    handle.spawn_default(async move {
+        let counter = Cell::new(0);
+        let counter = &counter;
+        let result = h.scope(|s| async move {
+            for i in 1..=3 {
+                s.spawn_default(async move {
+                    for _ in 0..i {
+                        counter.set(counter.get() + 1);
+                        yield_once!();
+                    }
+                }).unwrap();
+            }
+            //children still work after this future finishes
+            counter.get()
+        }).await;
+        assert_eq!(result, Ok(0));
+        assert_eq!(counter.get(), 6);
+        h.cancel(other);
+    }).unwrap();
+    smol::block_on(wheel).unwrap();
+    assert!(outer_polls.get() >= 3); //other tasks of outer wheel run while scope is active
+}
+
+#[test]
+fn test_scope_suspended_children() {
+    let wheel = Wheel::new();
+    let handle = wheel.handle().clone();
+    let h = handle.clone();
+    handle.spawn_default(async move {
+        let result = h.scope(|s| async move {
+            s.spawn(SpawnParams::suspended(true), async {}).unwrap();
+            yield_once!();
+        }).await;
+        assert_eq!(result, Err(SuspendError));
+    }).unwrap();
+    smol::block_on(wheel).unwrap();
+}
+
+struct SetOnDrop(Rc<Cell<bool>>);
+impl Drop for SetOnDrop {
+    fn drop(&mut self) { self.0.set(true) }
+}
+
+#[test]
+fn test_scope_cancelled_with_parent() {
+    let dropped = Rc::new(Cell::new(false));
+    let wheel = Wheel::new();
+    let handle = wheel.handle().clone();
+    let (h, d) = (handle.clone(), dropped.clone());
+    let parent = handle.spawn_default(async move {
+        let local = Cell::new(0);
+        let local = &local;
+        let _ = h.scope(|s| async move {
+            s.spawn_default(async move {
+                let _guard = SetOnDrop(d);
+                loop {
+                    local.set(local.get() + 1);
+                    yield_once!();
+                }
+            }).unwrap();
+        }).await;
+        unreachable!("Scope never finishes.");
+    }).unwrap();
+    let h = handle.clone();
+    handle.spawn_default(async move {
+        Yield::times(5).await;
+        h.cancel(parent);
+    }).unwrap();
+    smol::block_on(wheel).unwrap();
+    assert!(dropped.get()); //nested task was dropped together with parent
+}
@@ -0,0 +1,56 @@
+mod common;
+pub use common::*;
+use juggle::dy::*;
+use juggle::*;
+use std::cell::Cell;
+
+#[test]
+fn test_snapshot_states() {
+    let signal = Signal::new();
+    let wheel = Wheel::new();
+    let handle = wheel.handle().clone();
+    let waiting = handle.spawn(SpawnParams::named("Waiting").tag("io").tag("net").tag("io"), signal.clone()).unwrap();
+    let suspended = handle.spawn(SpawnParams::named("Suspended").suspend(true), async {}).unwrap();
+    let runnable = handle.spawn(SpawnParams::dyn_named("Runnable"), Yield::times(100)).unwrap();
+    let reached = &Cell::new(false);
+    let (h, sig) = (handle.clone(), signal.clone());
+    let ctrl = handle.spawn_default(async move {
+        let (handle, signal) = (h, sig);
+        yield_once!();
+        let snapshot = handle.snapshot().unwrap();
+        assert_eq!(snapshot.current, handle.current());
+        assert_eq!(snapshot.tasks.len(), 4);
+        assert_eq!(snapshot.get(waiting).unwrap().state, State::Waiting);
+        assert_eq!(snapshot.get(suspended).unwrap().state, State::Suspended);
+        assert_eq!(snapshot.get(runnable).unwrap().state, State::Runnable);
+        assert_eq!(snapshot.get(runnable).unwrap().name.as_deref(), Some("Runnable"));
+        let current = snapshot.get(handle.current().unwrap()).unwrap();
+        assert!(current.current);
+        assert_eq!(current.name, None);
+        assert_eq!(snapshot.with_state(State::Runnable).count(), 2);
+        assert_eq!(snapshot.get(waiting).unwrap().tags, vec!["io", "net"]);
+        assert_eq!(snapshot.with_tag("net").map(|t| t.id).collect::<Vec<_>>(), vec![waiting]);
+        assert!(snapshot.get(runnable).unwrap().tags.is_empty());
+        assert!(handle.tasks().eq(snapshot.clone().into_iter()));
+
+        let debug = format!("{:?}", handle);
+        assert!(debug.contains("waiting: [0x0:Waiting]"), "{}", debug);
+        assert!(debug.contains("suspended: [0x1:Suspended]"), "{}", debug);
+        assert!(debug.contains("runnable: [0x2:Runnable, 0x3]"), "{}", debug);
+        assert!(debug.contains("current: 0x3"), "{}", debug);
+
+        handle.cancel(suspended);
+        let debug = format!("{:?}", handle);
+        assert!(debug.contains("cancelled: [0x1:Suspended]"), "{}", debug);
+        assert!(debug.contains("suspended: None"), "{}", debug);
+        signal.signal(true);
+        reached.set(true);
+    }).unwrap();
+    assert_eq!(wheel.handle().snapshot().unwrap().current, None);
+    assert_eq!(wheel.handle().tasks().map(|t| t.id).collect::<Vec<_>>(), vec![waiting, suspended, runnable, ctrl]);
+
+    smol::block_on(wheel).unwrap();
+    assert!(reached.get());
+    assert_eq!(handle.snapshot(), None);
+    assert_eq!(handle.tasks().count(), 0);
+}
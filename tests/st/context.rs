@@ -0,0 +1,30 @@
+use juggle::st::{StaticHandle, StaticParams, StaticWheelDef};
+use std::cell::Cell;
+
+struct Config {
+    step: u32,
+}
+
+async fn add(handle: StaticHandle) {
+    let step = handle.with_context(|config: &Config| config.step).unwrap();
+    let total = handle.context::<Cell<u32>>().unwrap();
+    total.set(total.get() + step);
+    assert!(handle.context::<String>().is_none()); //not provided
+}
+
+static CONTEXT: StaticWheelDef = boxed_config! {
+    (handle) StaticParams::new() => add(handle),
+    (handle) StaticParams::new() => add(handle)
+};
+
+#[test]
+fn test_static_context() {
+    let wheel = CONTEXT.lock();
+    assert!(wheel.provide(Config { step: 2 }).is_none());
+    assert!(wheel.provide(Cell::new(1u32)).is_none());
+    let total = wheel.handle().context::<Cell<u32>>().unwrap();
+    let previous = wheel.provide(Config { step: 3 }).unwrap(); //replaced before tasks run
+    assert_eq!(previous.step, 2);
+    smol::block_on(wheel).unwrap();
+    assert_eq!(total.get(), 7);
+}
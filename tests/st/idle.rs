@@ -0,0 +1,30 @@
+use juggle::st::{StaticHandle, StaticParams, StaticWheelDef};
+use juggle::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static COUNTER: AtomicUsize = AtomicUsize::new(0);
+static OBSERVED: AtomicUsize = AtomicUsize::new(0);
+
+async fn count(times: usize) {
+    for _ in 0..times {
+        COUNTER.fetch_add(1, Ordering::SeqCst);
+        yield_once!();
+    }
+}
+
+async fn observe(handle: StaticHandle) {
+    handle.wait_idle().await;
+    OBSERVED.store(COUNTER.load(Ordering::SeqCst), Ordering::SeqCst);
+}
+
+static IDLE: StaticWheelDef = boxed_config! {
+    (_h) StaticParams::new() => count(10),
+    (_h) StaticParams::new() => count(20),
+    (handle) StaticParams::new() => observe(handle)
+};
+
+#[test]
+fn test_static_wait_idle() {
+    smol::block_on(IDLE.lock()).unwrap();
+    assert_eq!(OBSERVED.load(Ordering::SeqCst), 30); //other tasks finished before idle beat
+}
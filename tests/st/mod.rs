@@ -1,6 +1,32 @@
+/// Same as `static_config!` but tasks are boxed, so that it doesn't need unstable
+/// `type_alias_impl_trait` feature.
+macro_rules! boxed_config {
+    ($(($handle_var:ident) $params_expr:expr => $async_expr:expr),*) => {{
+        static ARRAY: [juggle::macro_private::StaticFuture; [$(boxed_config!(@unit $async_expr)),*].len()] = [$(
+            juggle::macro_private::StaticFuture::new({
+                type TaskType = core::pin::Pin<Box<dyn core::future::Future<Output=()> + 'static>>;
+                fn wrapper($handle_var: juggle::st::StaticHandle) -> TaskType { Box::pin($async_expr) }
+                juggle::macro_private::FnPtrWrapper(|handle, cx, status| {
+                    static mut POLL: core::mem::MaybeUninit<TaskType> = core::mem::MaybeUninit::uninit();
+                    static mut INIT_FLAG: u8 = 0;
+                    unsafe {
+                        juggle::macro_private::handle_task(&mut *core::ptr::addr_of_mut!(POLL),
+                            &mut *core::ptr::addr_of_mut!(INIT_FLAG), status, move || wrapper(handle), cx)
+                    }
+                })
+            }, $params_expr)),*];
+        juggle::macro_private::config_static(&ARRAY)
+    }};
+    (@unit $e:expr) => { () };
+}
+
 mod simple;
+mod restart;
+mod idle;
+mod suspend_self;
+mod context;
 
 #[test]
 pub fn REMOVE_this_test(){
 
-}
\ No newline at end of file
+}
@@ -0,0 +1,37 @@
+use juggle::st::{RestartPolicy, StaticHandle, StaticParams, StaticWheelDef};
+use juggle::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static STARTS: [AtomicUsize; 3] = [AtomicUsize::new(0), AtomicUsize::new(0), AtomicUsize::new(0)];
+
+async fn count_starts(index: usize) {
+    STARTS[index].fetch_add(1, Ordering::SeqCst);
+    loop { yield_once!(); }
+}
+
+async fn finish_after(index: usize, yields: usize) {
+    STARTS[index].fetch_add(1, Ordering::SeqCst);
+    Yield::times(yields).await;
+}
+
+async fn cancel_sibling(handle: StaticHandle) {
+    yield_once!();
+    handle.cancel(handle.get_by_name("sibling").unwrap()); //cancelled in the same beat as restart
+    Yield::times(10).await;
+    //group restart can't leave sibling counted twice, otherwise wheel would never finish
+    handle.cancel(handle.get_by_name("sibling").unwrap());
+}
+
+static GROUP: StaticWheelDef = boxed_config! {
+    (_h) StaticParams::named("leader").restart_policy(RestartPolicy::Times(2)).restart_group(1)
+        => finish_after(0, 1),
+    (_h) StaticParams::named("sibling").restart_group(1) => count_starts(1),
+    (handle) StaticParams::new() => cancel_sibling(handle)
+};
+
+#[test]
+fn test_restart_group_times() {
+    smol::block_on(GROUP.lock()).unwrap();
+    assert_eq!(STARTS[0].load(Ordering::SeqCst), 3); //started once and restarted two times
+    assert!(STARTS[1].load(Ordering::SeqCst) >= 2); //restarted together with leader
+}
@@ -0,0 +1,31 @@
+use juggle::st::{StaticHandle, StaticParams, StaticWheelDef};
+use std::sync::Mutex;
+
+static RECEIVED: Mutex<Vec<Option<u32>>> = Mutex::new(Vec::new());
+
+async fn worker(handle: StaticHandle) {
+    for _ in 0..2 {
+        let value = handle.suspend_self::<u32>().await;
+        RECEIVED.lock().unwrap().push(value);
+    }
+}
+
+async fn resumer(handle: StaticHandle) {
+    let worker = handle.get_by_name("worker").unwrap();
+    handle.wait_idle().await; //worker is suspended
+    assert!(handle.resume_with(worker, 5u32));
+    assert!(!handle.resume_with(worker, 6u32)); //not suspended anymore
+    handle.wait_idle().await;
+    assert!(handle.resume(worker)); //resumed without value
+}
+
+static SUSPEND: StaticWheelDef = boxed_config! {
+    (handle) StaticParams::named("worker") => worker(handle),
+    (handle) StaticParams::new() => resumer(handle)
+};
+
+#[test]
+fn test_static_suspend_self() {
+    smol::block_on(SUSPEND.lock()).unwrap();
+    assert_eq!(*RECEIVED.lock().unwrap(), vec![Some(5), None]);
+}
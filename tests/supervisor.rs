@@ -0,0 +1,188 @@
+use juggle::dy::*;
+use juggle::utils::TimerClock;
+use juggle::*;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::time::Duration;
+
+#[derive(Clone, Default)]
+struct ManualClock(Rc<Cell<Duration>>);
+
+impl TimerClock for ManualClock {
+    type Duration = Duration;
+    type Instant = Duration;
+    fn start(&self) -> Duration { self.0.get() }
+    fn stop(&self, start: Duration) -> Duration { self.0.get() - start }
+}
+
+#[test]
+fn test_restart_factory_task() {
+    let wheel = Wheel::new();
+    let handle = wheel.handle().clone();
+    let starts = Rc::new(Cell::new(0));
+    let s = starts.clone();
+    let id = handle.spawn_factory(SpawnParams::named("restarted"), move || {
+        s.set(s.get() + 1);
+        let count = s.get();
+        async move {
+            Yield::times(2).await;
+            assert_eq!(count, 3, "Previous futures should be dropped before they finish.");
+        }
+    }).unwrap();
+    let plain = handle.spawn_default(async { Yield::times(10).await; }).unwrap();
+    assert!(!handle.restart(plain));
+    let h = handle.clone();
+    handle.spawn_default(async move {
+        assert!(h.restart(id));
+        assert!(h.restart(id)); //restart is requested only once
+        yield_once!();
+        assert!(h.suspend(id));
+        assert!(h.restart(id)); //resumes task
+        assert_eq!(h.get_state(id), Some(State::Runnable));
+    }).unwrap();
+    smol::block_on(wheel).unwrap();
+    assert_eq!(starts.get(), 3);
+}
+
+#[test]
+fn test_restart_self() {
+    let wheel = Wheel::new();
+    let handle = wheel.handle().clone();
+    let starts = Rc::new(Cell::new(0));
+    let (h, s) = (handle.clone(), starts.clone());
+    handle.spawn_factory(SpawnParams::default(), move || {
+        s.set(s.get() + 1);
+        let (h, count) = (h.clone(), s.get());
+        async move {
+            if count < 5 {
+                assert!(h.restart(h.current().unwrap())); //restarted after it finishes
+            }
+        }
+    }).unwrap();
+    smol::block_on(wheel).unwrap();
+    assert_eq!(starts.get(), 5);
+}
+
+fn supervised(strategy: RestartStrategy) -> Vec<&'static str> {
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let failed = Rc::new(Cell::new(false));
+    let wheel = Wheel::new();
+    let handle = wheel.handle().clone();
+    let mut supervisor = Supervisor::new(strategy, ManualClock::default());
+    for &name in &["a", "b", "c"] {
+        let (log, failed) = (log.clone(), failed.clone());
+        supervisor = supervisor.child(SpawnParams::named(name), move || {
+            let (log, failed) = (log.clone(), failed.clone());
+            async move {
+                log.borrow_mut().push(name);
+                Yield::times(3).await;
+                if name == "b" && !failed.replace(true) {
+                    return Err(());
+                }
+                Yield::times(3).await;
+                Ok(())
+            }
+        });
+    }
+    assert_eq!(supervisor.child_count(), 3);
+    let h = handle.clone();
+    handle.spawn_default(async move {
+        assert_eq!(supervisor.run(h).await, Ok(()));
+    }).unwrap();
+    smol::block_on(wheel).unwrap();
+    let mut log = log.borrow().clone();
+    log.sort();
+    log
+}
+
+#[test]
+fn test_supervisor_strategies() {
+    assert_eq!(supervised(RestartStrategy::OneForOne), vec!["a", "b", "b", "c"]);
+    assert_eq!(supervised(RestartStrategy::OneForAll), vec!["a", "a", "b", "b", "c", "c"]);
+    assert_eq!(supervised(RestartStrategy::RestForOne), vec!["a", "b", "b", "c", "c"]);
+}
+
+#[test]
+fn test_supervisor_intensity_and_backoff() {
+    let clock = ManualClock::default();
+    let starts = Rc::new(RefCell::new(Vec::new()));
+    let wheel = Wheel::new();
+    let handle = wheel.handle().clone();
+    let (c, s) = (clock.clone(), starts.clone());
+    let supervisor = Supervisor::new(RestartStrategy::OneForOne, clock.clone())
+        .intensity(2, Duration::from_millis(100))
+        .backoff(Duration::from_millis(10))
+        .child(SpawnParams::default(), move || {
+            s.borrow_mut().push(c.0.get().as_millis());
+            async { Err::<(), _>("crash") }
+        });
+    let h = handle.clone();
+    let result = Rc::new(Cell::new(None));
+    let r = result.clone();
+    handle.spawn_default(async move {
+        r.set(Some(supervisor.run(h).await));
+    }).unwrap();
+    let (c, r) = (clock.clone(), result.clone());
+    handle.spawn_default(async move {
+        while r.get().is_none() {
+            c.0.set(c.0.get() + Duration::from_millis(1));
+            yield_once!();
+        }
+    }).unwrap();
+    smol::block_on(wheel).unwrap();
+    assert_eq!(result.get(), Some(Err(RestartLimitError)));
+    let starts = starts.borrow();
+    assert_eq!(starts.len(), 3); //first start and two restarts
+    assert!(starts[1] - starts[0] >= 10 && starts[2] - starts[1] >= 10);
+}
+
+#[test]
+fn test_supervisor_child_cancelled_outside() {
+    let wheel = Wheel::new();
+    let handle = wheel.handle().clone();
+    let supervisor = Supervisor::new(RestartStrategy::OneForAll, ManualClock::default())
+        .child(SpawnParams::named("forever"), || async {
+            std::future::pending::<()>().await;
+            Ok::<(), ()>(())
+        })
+        .child(SpawnParams::named("short"), || async { Ok::<(), ()>(()) });
+    let h = handle.clone();
+    let done = Rc::new(Cell::new(false));
+    let d = done.clone();
+    handle.spawn_default(async move {
+        assert_eq!(supervisor.run(h).await, Ok(())); //doesn't wait for cancelled child forever
+        d.set(true);
+    }).unwrap();
+    let h = handle.clone();
+    handle.spawn_default(async move {
+        Yield::times(3).await;
+        assert!(h.cancel(h.get_by_name("forever").unwrap()));
+    }).unwrap();
+    smol::block_on(wheel).unwrap();
+    assert!(done.get());
+}
+
+#[test]
+fn test_supervisor_backoff_timer() {
+    let slept = Rc::new(RefCell::new(Vec::new()));
+    let failed = Rc::new(Cell::new(false));
+    let wheel = Wheel::new();
+    let handle = wheel.handle().clone();
+    let s = slept.clone();
+    let supervisor = Supervisor::new(RestartStrategy::OneForOne, ManualClock::default())
+        .backoff(Duration::from_millis(5))
+        .timer(move |delay| {
+            s.borrow_mut().push(delay);
+            async move { smol::Timer::after(delay).await; }
+        })
+        .child(SpawnParams::default(), move || {
+            let failed = failed.clone();
+            async move { if failed.replace(true) { Ok(()) } else { Err(()) } }
+        });
+    let h = handle.clone();
+    handle.spawn_default(async move {
+        assert_eq!(supervisor.run(h).await, Ok(()));
+    }).unwrap();
+    smol::block_on(wheel).unwrap();
+    assert_eq!(*slept.borrow(), vec![Duration::from_millis(5)]); //waited by timer, not by manual clock
+}
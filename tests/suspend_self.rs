@@ -0,0 +1,52 @@
+use juggle::dy::*;
+use juggle::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[test]
+fn test_suspend_self_values() {
+    let wheel = Wheel::new();
+    let handle = wheel.handle().clone();
+    let received = Rc::new(RefCell::new(Vec::new()));
+    let (h, r) = (handle.clone(), received.clone());
+    let waiting = handle.spawn_default(async move {
+        for _ in 0..3 {
+            let value: Option<&'static str> = h.suspend_self().await;
+            r.borrow_mut().push(value);
+        }
+    }).unwrap();
+    let h = handle.clone();
+    handle.spawn_default(async move {
+        yield_once!();
+        assert_eq!(h.get_state(waiting), Some(State::Suspended));
+        assert!(h.resume_with(waiting, "first"));
+        assert!(!h.resume_with(waiting, "second")); //not suspended anymore
+        yield_once!();
+        assert!(h.resume(waiting)); //without value
+        yield_once!();
+        assert!(h.resume_with(waiting, 5u32)); //value of different type
+    }).unwrap();
+    smol::block_on(wheel).unwrap();
+    assert_eq!(*received.borrow(), vec![Some("first"), None, None]);
+}
+
+#[test]
+fn test_suspend_self_outside_task() {
+    let wheel = Wheel::new();
+    let handle = wheel.handle().clone();
+    assert_eq!(smol::block_on(handle.suspend_self::<u32>()), None);
+    drop(wheel);
+    assert_eq!(smol::block_on(handle.suspend_self::<u32>()), None);
+}
+
+#[test]
+fn test_all_suspended_by_self() {
+    let wheel = Wheel::new();
+    let handle = wheel.handle().clone();
+    let h = handle.clone();
+    handle.spawn_default(async move {
+        h.suspend_self::<()>().await;
+        unreachable!("Task is never resumed.");
+    }).unwrap();
+    assert_eq!(smol::block_on(wheel), Err(SuspendError));
+}
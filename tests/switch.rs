@@ -0,0 +1,116 @@
+use juggle::dy::*;
+use juggle::*;
+use std::cell::{Cell, RefCell};
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+
+/// Yields once without waking, so task is polled again only when someone switches to it.
+struct Park(bool);
+impl Future for Park {
+    type Output = ();
+    fn poll(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<()> {
+        if self.0 { Poll::Ready(()) } else {
+            self.0 = true;
+            Poll::Pending
+        }
+    }
+}
+
+#[test]
+fn test_switch_to_handoff() {
+    let wheel = Wheel::with_policy(FifoPolicy::new());
+    let handle = wheel.handle().clone();
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let slot = Rc::new(Cell::new(None));
+    let (l, s) = (log.clone(), slot.clone());
+    let consumer = handle.spawn_default(async move {
+        loop {
+            if let Some(item) = s.take() {
+                l.borrow_mut().push(format!("consume {}", item));
+            }
+            Park(false).await;
+        }
+    }).unwrap();
+    let (h, l, s) = (handle.clone(), log.clone(), slot.clone());
+    handle.spawn_default(async move {
+        for i in 0..3 {
+            l.borrow_mut().push(format!("produce {}", i));
+            s.set(Some(i));
+            assert!(h.switch_to(consumer).await);
+            l.borrow_mut().push(format!("back {}", i));
+        }
+        h.cancel(consumer);
+    }).unwrap();
+    let (h, l) = (handle.clone(), log.clone());
+    handle.spawn_default(async move {
+        for i in 0..3 {
+            l.borrow_mut().push(format!("other {}", i));
+            yield_once!();
+        }
+        assert!(!h.switch_to(h.current().unwrap()).await);
+    }).unwrap();
+    smol::block_on(wheel).unwrap();
+    assert_eq!(*log.borrow(), vec!["produce 0", "consume 0", "other 0", "back 0", "produce 1", "consume 1",
+                                   "other 1", "back 1", "produce 2", "consume 2", "other 2", "back 2"]);
+}
+
+#[test]
+fn test_switch_to_unavailable() {
+    let wheel = Wheel::new();
+    let handle = wheel.handle().clone();
+    let suspended = handle.spawn(SpawnParams::suspended(true), async {}).unwrap();
+    let polled = Rc::new(Cell::new(false));
+    let p = polled.clone();
+    let h = handle.clone();
+    handle.spawn_default(async move {
+        assert!(!h.switch_to(suspended).await);
+        p.set(true);
+        h.cancel(suspended);
+    }).unwrap();
+    smol::block_on(wheel).unwrap();
+    assert!(polled.get());
+    let invalid = handle.switch_to(suspended);
+    assert!(!smol::block_on(invalid));
+}
+
+#[test]
+fn test_switch_to_polls_once_per_beat() {
+    let wheel = Wheel::with_policy(FifoPolicy::new());
+    let handle = wheel.handle().clone();
+    let beat = Rc::new(Cell::new(0));
+    let b = beat.clone();
+    let ticker = handle.spawn_default(async move {
+        loop {
+            b.set(b.get() + 1);
+            yield_once!();
+        }
+    }).unwrap();
+    let polls = Rc::new(RefCell::new(Vec::new()));
+    let (b, p) = (beat.clone(), polls.clone());
+    let (h, ready) = (handle.clone(), Rc::new(Cell::new(None)));
+    let target_slot = ready.clone();
+    handle.spawn_default(async move {
+        while target_slot.get().is_none() {
+            yield_once!();
+        }
+        let target = target_slot.get().unwrap();
+        for _ in 0..3 {
+            assert!(h.switch_to(target).await);
+        }
+        h.cancel(target);
+        h.cancel(ticker);
+    }).unwrap();
+    let target = handle.spawn_default(async move {
+        loop {
+            p.borrow_mut().push(b.get());
+            yield_once!();
+        }
+    }).unwrap();
+    ready.set(Some(target));
+    smol::block_on(wheel).unwrap();
+    let polls = polls.borrow();
+    assert!(polls.len() >= 3);
+    assert!(polls.windows(2).all(|w| w[0] < w[1]), "polled twice in one beat: {:?}", *polls);
+}
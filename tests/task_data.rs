@@ -0,0 +1,72 @@
+use juggle::dy::*;
+use juggle::*;
+use std::cell::Cell;
+use std::rc::Rc;
+
+#[derive(Debug, PartialEq)]
+struct Request {
+    client: u32,
+    path: &'static str,
+}
+
+#[test]
+fn test_with_data() {
+    let wheel = Wheel::new();
+    let handle = wheel.handle().clone();
+    let params = SpawnParams::default().data(Request { client: 7, path: "/status" });
+    assert_eq!(params.get_data::<Request>().map(|r| r.client), Some(7));
+    assert_eq!(params.get_data::<u32>(), None);
+    let first = handle.spawn(params.clone(), async { yield_once!(); }).unwrap();
+    let second = handle.spawn(params, async { yield_once!(); }).unwrap();
+    let plain = handle.spawn_default(async {}).unwrap();
+    let h = handle.clone();
+    handle.spawn_default(async move {
+        assert_eq!(h.with_data(first, |r: &Request| r.path), Some("/status"));
+        //data is shared by tasks spawned with cloned parameters
+        assert_eq!(h.with_data(second, |r: &Request| r as *const Request),
+                   h.with_data(first, |r: &Request| r as *const Request));
+        assert_eq!(h.with_data(plain, |r: &Request| r.client), None);
+        //wheel can be used inside closure
+        let spawned = h.with_data(first, |r: &Request| {
+            let client = r.client;
+            h.spawn(SpawnParams::default().data(client + 1), async {}).unwrap()
+        }).unwrap();
+        assert_eq!(h.with_data(spawned, |c: &u32| *c), Some(8));
+    }).unwrap();
+    smol::block_on(wheel).unwrap();
+    assert_eq!(handle.with_data(first, |r: &Request| r.client), None); //invalid handle
+}
+
+#[test]
+fn test_local_data() {
+    let wheel = Wheel::new();
+    let handle = wheel.handle().clone();
+    let counter = Rc::new(Cell::new(0u32));
+    let local = handle.spawn_detachable(SpawnParams::suspended(true).data(counter.clone()), async {}).unwrap();
+    let shared = handle.spawn_detachable(SpawnParams::suspended(true).shared_data(5u32), async {}).unwrap();
+    assert!(handle.with_data(local, |c: &Rc<Cell<u32>>| c.set(c.get() + 1)).is_some());
+    assert_eq!(counter.get(), 1);
+    assert!(handle.detach(local).is_none()); //data is not Send
+    let task = handle.detach(shared).unwrap();
+    assert_eq!(task.params().get_data::<u32>(), Some(&5));
+    handle.cancel(local);
+    smol::block_on(wheel).unwrap();
+}
+
+#[test]
+fn test_set_name() {
+    let wheel = Wheel::new();
+    let handle = wheel.handle().clone();
+    let id = handle.spawn(SpawnParams::named("idle"), async { yield_once!(); }).unwrap();
+    let unnamed = handle.spawn_default(async { yield_once!(); }).unwrap();
+    assert!(handle.set_dyn_name(id, format!("worker {}", 1)));
+    assert_eq!(handle.get_name(id).as_deref(), Some("worker 1"));
+    assert!(handle.set_name(unnamed, "named later"));
+    assert_eq!(handle.get_by_name("named later"), Some(unnamed));
+    assert_eq!(handle.snapshot().unwrap().get(id).unwrap().name.as_deref(), Some("worker 1"));
+    //name can't be changed while it's borrowed
+    assert!(!handle.with_name(id, |_| handle.set_name(id, "other")));
+    assert_eq!(handle.get_name(id).as_deref(), Some("worker 1"));
+    smol::block_on(wheel).unwrap();
+    assert!(!handle.set_name(id, "finished"));
+}
@@ -31,4 +31,16 @@ fn test_simple() {
     assert!(!group.can_execute(k1) && group.can_execute(k2));
     group.update_duration(k2, 10);
     assert!(group.can_execute(k1) && group.can_execute(k2));
-}
\ No newline at end of file
+}
+#[test]
+fn test_set_slot_count() {
+    let mut group = TimingGroup::new();
+    let k1 = group.insert(1);
+    let k2 = group.insert(1);
+    group.update_duration(k1, 20);
+    group.update_duration(k2, 10);
+    assert!(!group.can_execute(k1) && group.can_execute(k2));
+    group.set_slot_count(k1, 2);
+    assert_eq!(group.get_slot_count(k1), Some(2));
+    assert!(group.can_execute(k1) && group.can_execute(k2));
+}
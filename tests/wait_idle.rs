@@ -0,0 +1,93 @@
+use juggle::dy::*;
+use juggle::*;
+use std::cell::{Cell, RefCell};
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll, Waker};
+
+/// Event that is set by hand, tasks awaiting it are idle.
+#[derive(Default)]
+struct Event {
+    set: Cell<bool>,
+    wakers: RefCell<Vec<Waker>>,
+}
+impl Event {
+    fn set(&self) {
+        self.set.set(true);
+        self.wakers.borrow_mut().drain(..).for_each(Waker::wake);
+    }
+    fn wait(&self) -> impl Future<Output=()> + '_ {
+        struct Wait<'a>(&'a Event);
+        impl Future for Wait<'_> {
+            type Output = ();
+            fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+                if self.0.set.get() { return Poll::Ready(()); }
+                self.0.wakers.borrow_mut().push(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+        Wait(self)
+    }
+}
+
+#[test]
+fn test_wait_idle() {
+    let event = Rc::new(Event::default());
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let wheel = Wheel::new();
+    let handle = wheel.handle().clone();
+    for i in 0..3 {
+        let (e, l) = (event.clone(), log.clone());
+        handle.spawn_default(async move {
+            Yield::times(i * 2).await;
+            l.borrow_mut().push(format!("blocked {}", i));
+            e.wait().await;
+            l.borrow_mut().push(format!("done {}", i));
+        }).unwrap();
+    }
+    let suspended = handle.spawn(SpawnParams::suspended(true), async {}).unwrap();
+    for i in 0..2 {
+        let (h, e, l) = (handle.clone(), event.clone(), log.clone());
+        handle.spawn_default(async move {
+            h.wait_idle().await;
+            l.borrow_mut().push(format!("idle {}", i));
+            if i == 1 {
+                e.set();
+                h.cancel(suspended);
+            }
+        }).unwrap();
+    }
+    smol::block_on(wheel).unwrap();
+    let log = log.borrow();
+    assert_eq!(&log[..5], &["blocked 0", "blocked 1", "blocked 2", "idle 0", "idle 1"]);
+    assert_eq!(log.len(), 8);
+}
+
+#[test]
+fn test_wait_idle_alone() {
+    let wheel = Wheel::new();
+    let handle = wheel.handle().clone();
+    let h = handle.clone();
+    handle.spawn_default(async move {
+        h.wait_idle().await;
+        h.wait_idle().await;
+    }).unwrap();
+    smol::block_on(wheel).unwrap();
+    assert_eq!(smol::block_on(handle.wait_idle()), ()); //invalid handle
+}
+
+#[test]
+fn test_wait_idle_poll_for() {
+    let wheel = Wheel::new();
+    let handle = wheel.handle().clone();
+    let done = Rc::new(Cell::new(false));
+    let (h, d) = (handle.clone(), done.clone());
+    handle.spawn_default(async move {
+        h.wait_idle().await;
+        d.set(true);
+    }).unwrap();
+    let clock = juggle::utils::StdTimerClock;
+    while wheel.poll_for(std::time::Duration::from_secs(1), &clock) {}
+    assert!(done.get());
+}
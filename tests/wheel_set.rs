@@ -0,0 +1,102 @@
+use juggle::dy::*;
+use juggle::*;
+use std::sync::mpsc::channel;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn test_ping_pong_between_wheels() {
+    let (addr_tx, addr_rx) = channel();
+    let set = WheelSet::new(3, move |index, handle: &WheelHandle<'static>, set: &WheelSetHandle<(TaskAddr, u32)>| {
+        let (set, addr_tx) = (set.clone(), addr_tx.clone());
+        handle.spawn(SpawnParams::named("ponger"), async move {
+            let me = set.current().unwrap();
+            assert_eq!(me.wheel, index);
+            addr_tx.send(me).unwrap();
+            //reply with incremented value
+            while let Some((from, value)) = set.receive().await {
+                set.send(from, (me, value + 1));
+            }
+        }).unwrap();
+    });
+    let mut pongers: Vec<TaskAddr> = (0..3).map(|_| addr_rx.recv().unwrap()).collect();
+    pongers.sort();
+    assert_eq!(pongers.iter().map(|a| a.wheel).collect::<Vec<_>>(), vec![0, 1, 2]);
+    let (out_tx, out_rx) = channel();
+    let handle = set.handle().clone();
+    let targets = pongers.clone();
+    let pinger = set.handle().spawn_on(0, async move {
+        let me = handle.current().unwrap();
+        let mut value = 0;
+        for _ in 0..3 {
+            for &target in &targets {
+                handle.send(target, (me, value));
+                let (from, reply) = handle.receive().await.unwrap();
+                assert_eq!(from, target);
+                value = reply;
+            }
+        }
+        out_tx.send(value).unwrap();
+    });
+    assert_eq!(pinger.wait().map(|addr| addr.wheel), Some(0));
+    assert_eq!(out_rx.recv().unwrap(), 9);
+    assert_eq!(set.handle().spawn_on(3, async {}).wait(), None); //no such wheel
+    set.shutdown();
+    assert_eq!(set.join(), vec![Ok(()), Ok(()), Ok(())]);
+}
+
+#[test]
+fn test_cancel_and_finish() {
+    let cancelled = Arc::new(Mutex::new(false));
+    let (addr_tx, addr_rx) = channel();
+    let set: WheelSet = WheelSet::new(2, |_, _: &WheelHandle<'static>, _: &WheelSetHandle| {});
+    let (handle, c) = (set.handle().clone(), cancelled.clone());
+    let spawned = set.handle().spawn_on(1, async move {
+        let _flag = Flag(c);
+        addr_tx.send(handle.current().unwrap()).unwrap();
+        loop { handle.receive().await; }
+    });
+    let addr = addr_rx.recv().unwrap();
+    assert_eq!(spawned.wait(), Some(addr));
+    assert!(set.handle().cancel(addr));
+    while !*cancelled.lock().unwrap() {
+        std::thread::yield_now();
+    }
+    assert_eq!(smol::block_on(set.handle().receive()), None); //not inside wheel of set
+    let handle = set.handle().clone();
+    set.shutdown();
+    assert_eq!(set.join().len(), 2);
+    assert!(!handle.send(addr, ()));
+    assert_eq!(handle.spawn_on(0, async {}).wait(), None); //wheel has finished
+}
+
+struct Flag(Arc<Mutex<bool>>);
+impl Drop for Flag {
+    fn drop(&mut self) { *self.0.lock().unwrap() = true; }
+}
+
+#[test]
+fn test_messages_of_removed_task() {
+    let set: WheelSet<u32> = WheelSet::new(1, |_, _: &WheelHandle<'static>, _: &WheelSetHandle<u32>| {});
+    let removed = Arc::new(Mutex::new(false));
+    let flag = Flag(removed.clone()); //task can be cancelled before it's polled
+    let first = set.handle().spawn_on(0, async move {
+        let _flag = flag;
+        std::future::pending::<()>().await;
+    }).wait().unwrap();
+    assert!(set.handle().send(first, 1)); //never received
+    assert!(set.handle().cancel(first));
+    while !*removed.lock().unwrap() {
+        std::thread::yield_now();
+    }
+    let (out_tx, out_rx) = channel();
+    let handle = set.handle().clone();
+    let second = set.handle().spawn_on(0, async move {
+        let me = handle.current().unwrap();
+        handle.send(me, 2);
+        out_tx.send(handle.receive().await).unwrap();
+    });
+    assert_eq!(smol::block_on(second), Some(first)); //completion can be awaited, id was reused
+    assert_eq!(out_rx.recv().unwrap(), Some(2)); //message of removed task was discarded
+    set.shutdown();
+    assert_eq!(set.join(), vec![Ok(())]);
+}
@@ -0,0 +1,62 @@
+use juggle::dy::*;
+use juggle::*;
+use std::cell::Cell;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
+
+mod common;
+
+#[test]
+fn test_submit_from_threads() {
+    let wheel = Arc::new(WheelThread::spawn(|handle: &WheelHandle<'static>| {
+        //task that is not Send, spawned on wheel thread
+        let counter = Rc::new(Cell::new(0));
+        handle.spawn_default(async move {
+            loop {
+                counter.set(counter.get() + 1);
+                yield_once!();
+            }
+        }).unwrap();
+    }));
+    let threads: Vec<_> = (0..4u32).map(|i| {
+        let wheel = wheel.clone();
+        std::thread::spawn(move || {
+            let results: Vec<_> = (0..10u32).map(|j| wheel.submit(async move {
+                Yield::times(j as usize).await;
+                i * 100 + j
+            })).collect();
+            results.into_iter().map(|c| c.wait().unwrap()).sum::<u32>()
+        })
+    }).collect();
+    let sum: u32 = threads.into_iter().map(|t| t.join().unwrap()).sum();
+    assert_eq!(sum, (0..4).map(|i| i * 1000 + 45).sum());
+    let wheel = Arc::try_unwrap(wheel).unwrap();
+    wheel.shutdown();
+    assert_eq!(wheel.join(), Ok(()));
+}
+
+#[test]
+fn test_shutdown_cancels_tasks() {
+    let wheel = WheelThread::spawn(|_: &WheelHandle<'static>| {});
+    let waiting = wheel.submit(common::signal_after(Duration::from_secs(60)));
+    let done = wheel.submit_with(SpawnParams::named("quick"), async { 5 });
+    assert_eq!(done.wait(), Some(5));
+    assert!(!waiting.is_finished());
+    wheel.shutdown();
+    assert_eq!(waiting.wait(), None); //cancelled
+    assert!(wheel.submit(async {}).wait().is_none()); //never executed
+    assert_eq!(wheel.join(), Ok(()));
+}
+
+#[test]
+fn test_exit_value() {
+    let wheel = WheelThread::spawn(|handle: &WheelHandle<'static, u32>| {
+        let h = handle.clone();
+        handle.spawn_default(async move {
+            Yield::times(5).await;
+            h.exit(42);
+        }).unwrap();
+    });
+    assert_eq!(wheel.join(), Ok(42));
+}